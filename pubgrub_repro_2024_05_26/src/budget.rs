@@ -0,0 +1,162 @@
+use std::{
+    borrow::Borrow,
+    cell::Cell,
+    error::Error as StdError,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use hexpm::version::Version;
+use pubgrub::solver::{Dependencies, DependencyProvider};
+
+use crate::{PackageName, PubgrubRange};
+
+/// Limits on how long a resolution may run. Registries derived from real ones
+/// can keep pubgrub busy for a very long time, and a corpus run should report
+/// that rather than hang.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Budget {
+    pub timeout: Option<Duration>,
+    /// The most decisions plus `get_dependencies` calls allowed.
+    pub max_steps: Option<u64>,
+}
+
+/// Wraps a provider, failing resolution once its [`Budget`] is spent or it is
+/// cancelled. The failure surfaces as a [`BudgetExceeded`] error from whichever
+/// provider method noticed it, usually `should_cancel`.
+pub struct BudgetedDependencyProvider<DP> {
+    inner: DP,
+    budget: Budget,
+    started: Instant,
+    steps: Cell<u64>,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Cancels a resolution from another thread.
+#[derive(Debug, Clone)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{reason} after {steps} steps")]
+pub struct BudgetExceeded {
+    pub reason: Reason,
+    pub steps: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Timeout(Duration),
+    StepLimit(u64),
+    Cancelled,
+}
+
+impl Budget {
+    pub fn is_unlimited(&self) -> bool {
+        self.timeout.is_none() && self.max_steps.is_none()
+    }
+}
+
+impl<DP> BudgetedDependencyProvider<DP> {
+    /// The clock starts now, not when resolution starts.
+    pub fn new(inner: DP, budget: Budget) -> Self {
+        Self {
+            inner,
+            budget,
+            started: Instant::now(),
+            steps: Cell::new(0),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle(self.cancelled.clone())
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps.get()
+    }
+
+    pub fn into_inner(self) -> DP {
+        self.inner
+    }
+
+    fn check(&self) -> Result<(), BudgetExceeded> {
+        let exceeded = |reason| {
+            Err(BudgetExceeded {
+                reason,
+                steps: self.steps.get(),
+            })
+        };
+
+        if self.cancelled.load(Ordering::Relaxed) {
+            return exceeded(Reason::Cancelled);
+        }
+        if let Some(timeout) = self.budget.timeout {
+            if self.started.elapsed() > timeout {
+                return exceeded(Reason::Timeout(timeout));
+            }
+        }
+        Ok(())
+    }
+
+    fn step(&self) -> Result<(), BudgetExceeded> {
+        let steps = self.steps.get();
+        if let Some(max_steps) = self.budget.max_steps {
+            if steps >= max_steps {
+                return Err(BudgetExceeded {
+                    reason: Reason::StepLimit(max_steps),
+                    steps,
+                });
+            }
+        }
+        self.steps.set(steps + 1);
+        self.check()
+    }
+}
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<DP> DependencyProvider<PackageName, Version> for BudgetedDependencyProvider<DP>
+where
+    DP: DependencyProvider<PackageName, Version>,
+{
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn StdError>> {
+        self.step()?;
+        self.inner.choose_package_version(potential_packages)
+    }
+
+    fn get_dependencies(
+        &self,
+        name: &PackageName,
+        version: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn StdError>> {
+        self.step()?;
+        self.inner.get_dependencies(name, version)
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn StdError>> {
+        self.check()?;
+        self.inner.should_cancel()
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Timeout(timeout) => write!(f, "ran past the {timeout:?} timeout"),
+            Reason::StepLimit(max_steps) => write!(f, "hit the limit of {max_steps} steps"),
+            Reason::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! A registry as a DIMACS CNF formula, so an external SAT solver can say
+//! whether it has a solution without sharing any code with pubgrub.
+//!
+//! There is one variable per release. The root release must be chosen, at
+//! most one release of each package may be, and each chosen release implies
+//! one of the releases each of its requirements admits. Releases with unknown
+//! dependencies or that require their own package can never be chosen.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use hexpm::version::Version;
+
+use crate::{enumerate::Solution, registry::Registry, PackageName};
+
+/// A literal is a variable number, negated when the release is not chosen.
+pub type Clause = Vec<i32>;
+
+#[derive(Debug, Clone)]
+pub struct Cnf {
+    pub root: PackageName,
+    /// The release each variable stands for. Variable `n` is at index `n - 1`.
+    pub variables: Vec<(PackageName, Version)>,
+    pub clauses: Vec<Clause>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CnfError {
+    #[error("the solver could not decide: {0}")]
+    Undecided(String),
+    #[error("invalid literal `{0}` in the model")]
+    InvalidLiteral(String),
+    #[error("variable {0} is not in the formula")]
+    UnknownVariable(u32),
+    #[error("variable {0} is both true and false in the model")]
+    Contradiction(u32),
+    #[error("the model does not satisfy clause `{0}`")]
+    Unsatisfied(String),
+}
+
+/// Encodes the registry for resolving `root` at `root_version`.
+pub fn encode(registry: &Registry, root: &str, root_version: &Version) -> Cnf {
+    let mut variables = Vec::new();
+    let mut numbers = HashMap::new();
+    for (name, releases) in registry.packages() {
+        for version in releases.keys() {
+            variables.push((name.clone(), version.clone()));
+            let _ = numbers.insert((name.as_str(), version), variables.len() as i32);
+        }
+    }
+
+    let mut clauses = Vec::new();
+    match numbers.get(&(root, root_version)) {
+        Some(&root) => clauses.push(vec![root]),
+        // Nothing can satisfy the empty clause.
+        None => clauses.push(vec![]),
+    }
+
+    for (name, releases) in registry.packages() {
+        let own: Vec<_> = releases
+            .keys()
+            .map(|version| numbers[&(name.as_str(), version)])
+            .collect();
+        for (i, a) in own.iter().enumerate() {
+            for b in &own[i + 1..] {
+                clauses.push(vec![-a, -b]);
+            }
+        }
+
+        for (version, release) in releases {
+            let chosen = numbers[&(name.as_str(), version)];
+            let Some(requirements) = &release.requirements else {
+                clauses.push(vec![-chosen]);
+                continue;
+            };
+            if requirements.contains_key(name) {
+                clauses.push(vec![-chosen]);
+                continue;
+            }
+            for (dependency, requirement) in requirements {
+                let mut clause = vec![-chosen];
+                clause.extend(
+                    registry
+                        .releases(dependency)
+                        .into_iter()
+                        .flat_map(|releases| releases.keys())
+                        .filter(|version| requirement.range.contains(version))
+                        .map(|version| numbers[&(dependency.as_str(), version)]),
+                );
+                clauses.push(clause);
+            }
+        }
+    }
+
+    Cnf {
+        root: root.to_string(),
+        variables,
+        clauses,
+    }
+}
+
+impl Cnf {
+    /// The formula in DIMACS format, with a comment naming each variable's
+    /// release.
+    pub fn to_dimacs(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "c root {}", self.root);
+        for (i, (name, version)) in self.variables.iter().enumerate() {
+            let _ = writeln!(out, "c {} {name} {version}", i + 1);
+        }
+        let _ = writeln!(out, "p cnf {} {}", self.variables.len(), self.clauses.len());
+        for clause in &self.clauses {
+            let _ = writeln!(out, "{}", dimacs_clause(clause));
+        }
+        out
+    }
+
+    /// Reads a solver's answer, in either the competition format (`s` and `v`
+    /// lines) or MiniSat's (`SAT` then the literals). Returns `None` when the
+    /// formula is unsatisfiable, otherwise the releases the model chooses that
+    /// the root needs: a model may also choose releases nothing requires.
+    pub fn decode(&self, registry: &Registry, output: &str) -> Result<Option<Solution>, CnfError> {
+        let mut literals = Vec::new();
+        for line in output.lines().map(str::trim) {
+            let words = match line.split_once(' ') {
+                _ if line.is_empty() || line.starts_with('c') => continue,
+                Some(("s", status)) => match status.trim() {
+                    "SATISFIABLE" => continue,
+                    "UNSATISFIABLE" => return Ok(None),
+                    status => return Err(CnfError::Undecided(status.to_string())),
+                },
+                Some(("v", literals)) => literals,
+                _ => match line {
+                    "SAT" | "SATISFIABLE" => continue,
+                    "UNSAT" | "UNSATISFIABLE" => return Ok(None),
+                    "INDET" | "UNKNOWN" => return Err(CnfError::Undecided(line.to_string())),
+                    line => line,
+                },
+            };
+            for word in words.split_whitespace() {
+                let literal: i32 = word
+                    .parse()
+                    .map_err(|_| CnfError::InvalidLiteral(word.to_string()))?;
+                if literal != 0 {
+                    literals.push(literal);
+                }
+            }
+        }
+        self.check(&literals)
+            .map(|chosen| Some(self.required(registry, &chosen)))
+    }
+
+    // Every release the model chooses. Variables it leaves out are false.
+    fn check(&self, literals: &[i32]) -> Result<Solution, CnfError> {
+        let mut values = vec![None; self.variables.len()];
+        for &literal in literals {
+            let variable = literal.unsigned_abs();
+            let value = values
+                .get_mut(variable as usize - 1)
+                .ok_or(CnfError::UnknownVariable(variable))?;
+            if value.is_some_and(|value| value != (literal > 0)) {
+                return Err(CnfError::Contradiction(variable));
+            }
+            *value = Some(literal > 0);
+        }
+        let holds = |literal: &i32| {
+            values[literal.unsigned_abs() as usize - 1].unwrap_or(false) == (*literal > 0)
+        };
+        if let Some(clause) = self.clauses.iter().find(|clause| !clause.iter().any(holds)) {
+            return Err(CnfError::Unsatisfied(dimacs_clause(clause)));
+        }
+        Ok(self
+            .variables
+            .iter()
+            .zip(values)
+            .filter(|(_, value)| *value == Some(true))
+            .map(|((name, version), _)| (name.clone(), version.clone()))
+            .collect())
+    }
+
+    // The chosen releases reachable from the root through requirements.
+    pub(crate) fn required(&self, registry: &Registry, chosen: &Solution) -> Solution {
+        let mut required = BTreeMap::new();
+        let mut pending = vec![self.root.as_str()];
+        while let Some(name) = pending.pop() {
+            let Some(version) = chosen.get(name) else {
+                continue;
+            };
+            if required.insert(name.to_string(), version.clone()).is_some() {
+                continue;
+            }
+            let requirements = registry
+                .release(name, version)
+                .and_then(|release| release.requirements.as_ref());
+            pending.extend(
+                requirements
+                    .into_iter()
+                    .flat_map(|r| r.keys().map(String::as_str)),
+            );
+        }
+        required
+    }
+}
+
+fn dimacs_clause(clause: &[i32]) -> String {
+    let mut line = String::new();
+    for literal in clause {
+        let _ = write!(line, "{literal} ");
+    }
+    line.push('0');
+    line
+}
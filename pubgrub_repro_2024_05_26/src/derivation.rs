@@ -0,0 +1,220 @@
+//! pubgrub's derivation tree for a failed resolution as a graph, to tell a
+//! broken registry from a solver bug.
+//!
+//! Every node of the tree gets an id, root first. Derived incompatibilities
+//! pubgrub marks as shared appear once, however many times the tree refers to
+//! them.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+};
+
+use hexpm::version::Version;
+use pubgrub::report::{DerivationTree, External};
+use serde::Serialize;
+
+use crate::PackageName;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DerivationGraph {
+    /// Indexed by id. The root is node 0.
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Node {
+    pub id: usize,
+    #[serde(flatten)]
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NodeKind {
+    NotRoot {
+        package: PackageName,
+        version: String,
+    },
+    NoVersions {
+        package: PackageName,
+        range: String,
+    },
+    UnavailableDependencies {
+        package: PackageName,
+        range: String,
+    },
+    FromDependencyOf {
+        package: PackageName,
+        range: String,
+        dependency: PackageName,
+        dependency_range: String,
+    },
+    /// An incompatibility derived from two others: the terms cannot all hold.
+    Derived {
+        terms: BTreeMap<PackageName, String>,
+        shared_id: Option<usize>,
+        causes: [usize; 2],
+    },
+}
+
+impl DerivationGraph {
+    pub fn new(tree: &DerivationTree<PackageName, Version>) -> Self {
+        let mut graph = Self { nodes: Vec::new() };
+        let _ = graph.add(tree, &mut HashMap::new());
+        graph
+    }
+
+    // Numbers the node before its causes, so parents come first.
+    fn add(
+        &mut self,
+        tree: &DerivationTree<PackageName, Version>,
+        shared: &mut HashMap<usize, usize>,
+    ) -> usize {
+        let derived = match tree {
+            DerivationTree::External(external) => {
+                return self.push(external_kind(external));
+            }
+            DerivationTree::Derived(derived) => derived,
+        };
+        if let Some(&id) = derived
+            .shared_id
+            .and_then(|shared_id| shared.get(&shared_id))
+        {
+            return id;
+        }
+
+        let terms = derived
+            .terms
+            .iter()
+            .map(|(package, term)| (package.clone(), term.to_string()))
+            .collect();
+        let id = self.push(NodeKind::Derived {
+            terms,
+            shared_id: derived.shared_id,
+            causes: [0, 0],
+        });
+        if let Some(shared_id) = derived.shared_id {
+            let _ = shared.insert(shared_id, id);
+        }
+        let first = self.add(&derived.cause1, shared);
+        let second = self.add(&derived.cause2, shared);
+        if let NodeKind::Derived { causes, .. } = &mut self.nodes[id].kind {
+            *causes = [first, second];
+        }
+        id
+    }
+
+    fn push(&mut self, kind: NodeKind) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(Node { id, kind });
+        id
+    }
+
+    /// Externals are ellipses, derived incompatibilities boxes listing their
+    /// terms, and shared ones are doubled.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph derivation {{");
+        for node in &self.nodes {
+            let (shape, label) = match &node.kind {
+                NodeKind::Derived {
+                    terms, shared_id, ..
+                } => {
+                    let mut label: Vec<_> = terms
+                        .iter()
+                        .map(|(package, term)| format!("{package} {term}"))
+                        .collect();
+                    if label.is_empty() {
+                        label.push("no terms".into());
+                    }
+                    if let Some(shared_id) = shared_id {
+                        label.insert(0, format!("shared #{shared_id}"));
+                    }
+                    let shape = if shared_id.is_some() {
+                        "box, peripheries=2"
+                    } else {
+                        "box"
+                    };
+                    (shape, label.join("\n"))
+                }
+                kind => ("ellipse", kind.to_string()),
+            };
+            let _ = writeln!(
+                out,
+                "  n{} [shape={shape}, label=\"{}\"];",
+                node.id,
+                label
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n")
+            );
+        }
+        for node in &self.nodes {
+            if let NodeKind::Derived { causes, .. } = &node.kind {
+                for cause in causes {
+                    let _ = writeln!(out, "  n{} -> n{cause};", node.id);
+                }
+            }
+        }
+        let _ = writeln!(out, "}}");
+        out
+    }
+}
+
+fn external_kind(external: &External<PackageName, Version>) -> NodeKind {
+    match external {
+        External::NotRoot(package, version) => NodeKind::NotRoot {
+            package: package.clone(),
+            version: version.to_string(),
+        },
+        External::NoVersions(package, range) => NodeKind::NoVersions {
+            package: package.clone(),
+            range: range.to_string(),
+        },
+        External::UnavailableDependencies(package, range) => NodeKind::UnavailableDependencies {
+            package: package.clone(),
+            range: range.to_string(),
+        },
+        External::FromDependencyOf(package, range, dependency, dependency_range) => {
+            NodeKind::FromDependencyOf {
+                package: package.clone(),
+                range: range.to_string(),
+                dependency: dependency.clone(),
+                dependency_range: dependency_range.to_string(),
+            }
+        }
+    }
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeKind::NotRoot { package, version } => {
+                write!(f, "we are solving dependencies of {package} {version}")
+            }
+            NodeKind::NoVersions { package, range } => {
+                write!(f, "there is no version of {package} in {range}")
+            }
+            NodeKind::UnavailableDependencies { package, range } => {
+                write!(f, "dependencies of {package} {range} are unavailable")
+            }
+            NodeKind::FromDependencyOf {
+                package,
+                range,
+                dependency,
+                dependency_range,
+            } => write!(
+                f,
+                "{package} {range} depends on {dependency} {dependency_range}"
+            ),
+            NodeKind::Derived { terms, .. } => {
+                let terms: Vec<_> = terms
+                    .iter()
+                    .map(|(package, term)| format!("{package} {term}"))
+                    .collect();
+                write!(f, "not all of: {}", terms.join(", "))
+            }
+        }
+    }
+}
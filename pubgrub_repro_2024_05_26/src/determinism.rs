@@ -0,0 +1,134 @@
+//! Resolving the same fixture many times with everything pubgrub might
+//! iterate over in a different order, to tell a reliable repro from a flaky
+//! one.
+//!
+//! Each run is seeded. The seed picks the hasher of the provider's maps, the
+//! order releases are inserted into them, and the order and spare capacity of
+//! each dependency map handed to pubgrub. pubgrub's own maps use a fixed
+//! hasher, so capacity is what reorders their iteration. The versions of a
+//! package are still offered newest first: that order is a preference, not an
+//! accident.
+
+use std::{
+    cmp::Reverse,
+    collections::hash_map::DefaultHasher,
+    hash::{BuildHasher, Hasher},
+};
+
+use pubgrub::type_aliases::Map;
+
+use crate::{
+    budget::{Budget, BudgetedDependencyProvider},
+    fixture::Fixture,
+    issue_3201::Issue3201DependencyProvider,
+    outcome::{self, Outcome},
+    random::Random,
+    registry::Registry,
+};
+
+/// [`Issue3201DependencyProvider`] with its iteration orders picked by a seed.
+pub type ShuffledDependencyProvider = Issue3201DependencyProvider<SeededState>;
+
+/// SipHash keyed by a seed, standing in for a `RandomState` that can be
+/// replayed.
+#[derive(Debug, Clone, Copy)]
+pub struct SeededState(pub u64);
+
+/// Every run that ended the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    /// The outcome with run-specific detail removed: see [`normalise`].
+    pub outcome: String,
+    pub seeds: Vec<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Determinism {
+    pub runs: usize,
+    /// Most common first.
+    pub variants: Vec<Variant>,
+}
+
+/// Loads every release of `registry`, in an order picked by `seed`.
+pub fn shuffled(registry: &Registry, seed: u64) -> ShuffledDependencyProvider {
+    let mut random = Random::new(seed);
+    let mut releases: Vec<_> = registry
+        .packages()
+        .flat_map(|(name, releases)| releases.iter().map(move |release| (name, release)))
+        .collect();
+    random.shuffle(&mut releases);
+
+    let mut provider = ShuffledDependencyProvider::with_hashers(
+        SeededState(random.next_u64()),
+        SeededState(random.next_u64()),
+    );
+    for (name, (version, release)) in releases {
+        let dependencies = release.requirements.as_ref().map(|requirements| {
+            let mut requirements: Vec<_> = requirements.iter().collect();
+            random.shuffle(&mut requirements);
+            let spare = random.below(4 * requirements.len() + 1);
+            let mut dependencies =
+                Map::with_capacity_and_hasher(requirements.len() + spare, Default::default());
+            for (dependency, requirement) in requirements {
+                let _ = dependencies.insert(dependency.clone(), requirement.range.clone());
+            }
+            dependencies
+        });
+        provider.add_release(name, version, dependencies);
+    }
+    provider
+}
+
+impl BuildHasher for SeededState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.0);
+        hasher
+    }
+}
+
+/// Resolves `fixture` once per seed from `first_seed` on, `runs` in all.
+/// Panics are caught, but the panic hook still runs for each one.
+pub fn check(fixture: &Fixture, runs: usize, first_seed: u64, budget: Budget) -> Determinism {
+    let mut variants: Vec<Variant> = Vec::new();
+    for seed in (first_seed..).take(runs) {
+        let provider = BudgetedDependencyProvider::new(shuffled(&fixture.registry, seed), budget);
+        let outcome = normalise(&outcome::resolve(
+            &provider,
+            &fixture.root,
+            &fixture.root_version,
+        ));
+        match variants
+            .iter_mut()
+            .find(|variant| variant.outcome == outcome)
+        {
+            Some(variant) => variant.seeds.push(seed),
+            None => variants.push(Variant {
+                outcome,
+                seeds: vec![seed],
+            }),
+        }
+    }
+    variants.sort_by_key(|variant| Reverse(variant.seeds.len()));
+    Determinism { runs, variants }
+}
+
+/// The outcome with the detail that may legitimately differ between runs
+/// removed: the wording of pubgrub's explanation and how many steps a budget
+/// allowed. Solutions and error messages are kept in full.
+pub fn normalise(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::NoSolution(_) | Outcome::Unsatisfiable => "no solution".into(),
+        Outcome::TimedOut(exceeded) => format!("timed out: {}", exceeded.reason),
+        outcome => outcome.to_string().trim_end().to_string(),
+    }
+}
+
+impl Determinism {
+    /// Every run ended the same way.
+    pub fn is_deterministic(&self) -> bool {
+        self.variants.len() <= 1
+    }
+}
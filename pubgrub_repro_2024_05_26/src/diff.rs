@@ -0,0 +1,252 @@
+//! What changed between two resolutions, and which requirement pushed each
+//! package to its new version.
+
+use std::{collections::BTreeMap, fmt};
+
+use hexpm::version::Version;
+
+use crate::{
+    manifest::Manifest,
+    registry::{Registry, Requirement},
+    PackageName,
+};
+
+/// A set of chosen versions, with every requirement edge between them.
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    pub versions: BTreeMap<PackageName, Version>,
+    /// Keyed on the package required.
+    pub dependents: BTreeMap<PackageName, Vec<Edge>>,
+}
+
+/// One package requiring another.
+#[derive(Debug, Clone)]
+pub struct Edge {
+    /// `None` for the root project.
+    pub from: Option<(PackageName, Version)>,
+    /// `None` when only the name is known, as in a manifest read without a
+    /// registry.
+    pub requirement: Option<Requirement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub package: PackageName,
+    pub kind: ChangeKind,
+    pub old: Option<Version>,
+    pub new: Option<Version>,
+    /// For a package that is still there, the edges requiring it in the new
+    /// resolution. Otherwise the edges that required it in the old one.
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+}
+
+impl Resolution {
+    /// Ranges come from the registry where it has the release, otherwise only
+    /// the names in the manifest are known.
+    pub fn from_manifest(manifest: &Manifest, registry: Option<&Registry>) -> Self {
+        let mut resolution = Self::default();
+        for (name, spec) in &manifest.requirements {
+            resolution.add_edge(name, None, Requirement::parse(spec).ok());
+        }
+        for package in &manifest.packages {
+            let _ = resolution
+                .versions
+                .insert(package.name.clone(), package.version.clone());
+            let requirements = registry
+                .and_then(|registry| registry.release(&package.name, &package.version))
+                .and_then(|release| release.requirements.as_ref());
+            for dependency in &package.requirements {
+                resolution.add_edge(
+                    dependency,
+                    Some((package.name.clone(), package.version.clone())),
+                    requirements.and_then(|requirements| requirements.get(dependency).cloned()),
+                );
+            }
+        }
+        resolution
+    }
+
+    /// A solution from pubgrub, which includes the root package itself.
+    pub fn from_solution(
+        root: &str,
+        registry: &Registry,
+        solution: &BTreeMap<PackageName, Version>,
+    ) -> Self {
+        let mut resolution = Self::default();
+        for (name, version) in solution {
+            let from = if name == root {
+                None
+            } else {
+                let _ = resolution.versions.insert(name.clone(), version.clone());
+                Some((name.clone(), version.clone()))
+            };
+            let requirements = registry
+                .release(name, version)
+                .and_then(|release| release.requirements.as_ref());
+            for (dependency, requirement) in requirements.into_iter().flatten() {
+                resolution.add_edge(dependency, from.clone(), Some(requirement.clone()));
+            }
+        }
+        resolution
+    }
+
+    fn add_edge(
+        &mut self,
+        dependency: &str,
+        from: Option<(PackageName, Version)>,
+        requirement: Option<Requirement>,
+    ) {
+        self.dependents
+            .entry(dependency.to_string())
+            .or_default()
+            .push(Edge { from, requirement });
+    }
+
+    fn edges_to(&self, package: &str) -> Vec<Edge> {
+        self.dependents.get(package).cloned().unwrap_or_default()
+    }
+}
+
+impl Edge {
+    fn excludes(&self, version: &Version) -> bool {
+        self.requirement
+            .as_ref()
+            .is_some_and(|requirement| !requirement.range.contains(version))
+    }
+}
+
+impl Change {
+    /// The edges in the new resolution that rule out the old version, which is
+    /// what moved the package. Empty when it moved without being forced.
+    pub fn forcing_edges(&self) -> Vec<&Edge> {
+        match (&self.old, &self.kind) {
+            (Some(old), ChangeKind::Upgraded | ChangeKind::Downgraded) => self
+                .edges
+                .iter()
+                .filter(|edge| edge.excludes(old))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Every package whose version differs between the two, in name order.
+pub fn diff(old: &Resolution, new: &Resolution) -> Vec<Change> {
+    let mut names: Vec<_> = old.versions.keys().chain(new.versions.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let old_version = old.versions.get(name);
+            let new_version = new.versions.get(name);
+            let (kind, edges) = match (old_version, new_version) {
+                (None, Some(_)) => (ChangeKind::Added, new.edges_to(name)),
+                (Some(_), None) => (ChangeKind::Removed, old.edges_to(name)),
+                (Some(old), Some(new_version)) if new_version > old => {
+                    (ChangeKind::Upgraded, new.edges_to(name))
+                }
+                (Some(old), Some(new_version)) if new_version < old => {
+                    (ChangeKind::Downgraded, new.edges_to(name))
+                }
+                _ => return None,
+            };
+            Some(Change {
+                package: name.clone(),
+                kind,
+                old: old_version.cloned(),
+                new: new_version.cloned(),
+                edges,
+            })
+        })
+        .collect()
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = |version: &Option<Version>| {
+            version
+                .as_ref()
+                .map_or_else(String::new, Version::to_string)
+        };
+        match self.kind {
+            ChangeKind::Added => writeln!(f, "added {} {}", self.package, version(&self.new))?,
+            ChangeKind::Removed => writeln!(f, "removed {} {}", self.package, version(&self.old))?,
+            ChangeKind::Upgraded | ChangeKind::Downgraded => writeln!(
+                f,
+                "{} {} {} -> {}",
+                self.kind,
+                self.package,
+                version(&self.old),
+                version(&self.new)
+            )?,
+        }
+
+        match self.kind {
+            ChangeKind::Added => {
+                for edge in &self.edges {
+                    writeln!(f, "  required by {edge}")?;
+                }
+            }
+            ChangeKind::Removed => {
+                for edge in &self.edges {
+                    writeln!(f, "  was required by {edge}")?;
+                }
+            }
+            ChangeKind::Upgraded | ChangeKind::Downgraded => {
+                let forcing = self.forcing_edges();
+                for edge in &forcing {
+                    writeln!(f, "  forced by {edge}")?;
+                }
+                // Without its range, an edge may or may not have forced it.
+                let unknown: Vec<_> = self
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.requirement.is_none())
+                    .collect();
+                if forcing.is_empty() {
+                    for edge in &unknown {
+                        writeln!(f, "  required by {edge}, with an unknown requirement")?;
+                    }
+                    if unknown.is_empty() {
+                        writeln!(f, "  not forced by any requirement")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.from {
+            Some((name, version)) => write!(f, "{name} {version}")?,
+            None => write!(f, "the root")?,
+        }
+        match &self.requirement {
+            Some(requirement) => write!(f, " ({})", requirement.spec.as_str()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Added => write!(f, "added"),
+            ChangeKind::Removed => write!(f, "removed"),
+            ChangeKind::Upgraded => write!(f, "upgraded"),
+            ChangeKind::Downgraded => write!(f, "downgraded"),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Every valid solution of a registry, found by brute force rather than by
+//! pubgrub, to check its answers against.
+//!
+//! A solution picks one version of the root and of every package something in
+//! the solution requires, such that every requirement between them holds.
+//! Packages nothing requires are left out, and releases with unknown
+//! dependencies are never picked.
+
+use std::{collections::BTreeMap, ops::ControlFlow};
+
+use hexpm::version::Version;
+
+use crate::{
+    registry::{Registry, Requirement},
+    PackageName, PubgrubRange,
+};
+
+pub type Solution = BTreeMap<PackageName, Version>;
+
+/// The solutions found before stopping.
+#[derive(Debug, Clone, Default)]
+pub struct Solutions {
+    pub found: Vec<Solution>,
+    /// False when the search stopped at the cap, so there may be more.
+    pub complete: bool,
+}
+
+/// How many solutions there are, up to a cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Count {
+    pub count: u64,
+    /// False when counting stopped at the cap, so there may be more.
+    pub complete: bool,
+}
+
+/// Up to `cap` solutions, preferring newer versions of packages earlier in
+/// name order.
+pub fn solutions(registry: &Registry, root: &str, root_version: &Version, cap: usize) -> Solutions {
+    let mut found = Vec::new();
+    let flow = for_each_solution(registry, root, root_version, |solution| {
+        if found.len() == cap {
+            return ControlFlow::Break(());
+        }
+        found.push(solution.clone());
+        ControlFlow::Continue(())
+    });
+    Solutions {
+        found,
+        complete: flow.is_continue(),
+    }
+}
+
+/// The number of solutions, counting no further than `cap`.
+pub fn count(registry: &Registry, root: &str, root_version: &Version, cap: u64) -> Count {
+    let mut count = 0;
+    let flow = for_each_solution(registry, root, root_version, |_| {
+        if count == cap {
+            return ControlFlow::Break(());
+        }
+        count += 1;
+        ControlFlow::Continue(())
+    });
+    Count {
+        count,
+        complete: flow.is_continue(),
+    }
+}
+
+/// Calls `visit` with each solution in turn until it breaks.
+pub fn for_each_solution(
+    registry: &Registry,
+    root: &str,
+    root_version: &Version,
+    mut visit: impl FnMut(&Solution) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let Some(requirements) = registry
+        .release(root, root_version)
+        .and_then(|release| release.requirements.as_ref())
+    else {
+        return ControlFlow::Continue(());
+    };
+    let mut search = Search {
+        registry,
+        assignment: BTreeMap::from([(root.to_string(), root_version.clone())]),
+        visit: &mut visit,
+    };
+    match search.with_requirements(&BTreeMap::new(), root, requirements) {
+        Some(constraints) => search.next(constraints),
+        None => ControlFlow::Continue(()),
+    }
+}
+
+struct Search<'a, F> {
+    registry: &'a Registry,
+    assignment: Solution,
+    visit: &'a mut F,
+}
+
+impl<F: FnMut(&Solution) -> ControlFlow<()>> Search<'_, F> {
+    /// `constraints` holds the intersected requirements on every package
+    /// required so far, assigned or not.
+    fn next(&mut self, constraints: BTreeMap<PackageName, PubgrubRange>) -> ControlFlow<()> {
+        let Some((package, range)) = constraints
+            .iter()
+            .find(|(name, _)| !self.assignment.contains_key(*name))
+        else {
+            return (self.visit)(&self.assignment);
+        };
+
+        let Some(releases) = self.registry.releases(package) else {
+            return ControlFlow::Continue(());
+        };
+        for (version, release) in releases.iter().rev() {
+            if !range.contains(version) {
+                continue;
+            }
+            let Some(requirements) = &release.requirements else {
+                continue;
+            };
+            let Some(constraints) = self.with_requirements(&constraints, package, requirements)
+            else {
+                continue;
+            };
+
+            let _ = self.assignment.insert(package.clone(), version.clone());
+            let flow = self.next(constraints);
+            let _ = self.assignment.remove(package);
+            flow?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// The constraints once `package`'s requirements are added, or `None` if
+    /// they rule out an assigned version or every release of a dependency.
+    fn with_requirements(
+        &self,
+        constraints: &BTreeMap<PackageName, PubgrubRange>,
+        package: &str,
+        requirements: &BTreeMap<PackageName, Requirement>,
+    ) -> Option<BTreeMap<PackageName, PubgrubRange>> {
+        let mut constraints = constraints.clone();
+        for (dependency, requirement) in requirements {
+            if dependency == package {
+                return None;
+            }
+            let range = match constraints.get(dependency) {
+                Some(existing) => existing.intersection(&requirement.range),
+                None => requirement.range.clone(),
+            };
+            let satisfiable = match self.assignment.get(dependency) {
+                Some(assigned) => range.contains(assigned),
+                None => self
+                    .registry
+                    .releases(dependency)
+                    .is_some_and(|releases| releases.keys().any(|version| range.contains(version))),
+            };
+            if !satisfiable {
+                return None;
+            }
+            let _ = constraints.insert(dependency.clone(), range);
+        }
+        Some(constraints)
+    }
+}
@@ -0,0 +1,203 @@
+//! A provider wrapper that fails the way `gleam add` does on a flaky
+//! network: lookups that error, come back unknown or slow, and packages with
+//! no version to offer.
+//!
+//! Faults follow a seeded schedule, so a run that goes wrong can be replayed.
+//! Whatever is injected, pubgrub should end with a `PubGrubError`, never a
+//! panic.
+
+use std::{
+    borrow::Borrow,
+    cell::{Cell, RefCell},
+    error::Error as StdError,
+    thread,
+    time::Duration,
+};
+
+use hexpm::version::Version;
+use pubgrub::solver::{Dependencies, DependencyProvider};
+
+use crate::{
+    budget::{Budget, BudgetedDependencyProvider},
+    fixture::Fixture,
+    issue_3201::Issue3201DependencyProvider,
+    outcome::{self, Outcome},
+    random::Random,
+    PackageName, PubgrubRange,
+};
+
+/// How often each fault is injected, as a chance between 0 and 1 per call.
+#[derive(Debug, Clone, Copy)]
+pub struct Faults {
+    pub seed: u64,
+    /// `get_dependencies` fails with an [`InjectedFault`].
+    pub error: f64,
+    /// `get_dependencies` returns `Dependencies::Unknown`.
+    pub unknown: f64,
+    /// `get_dependencies` sleeps for `delay_for` first.
+    pub delay: f64,
+    pub delay_for: Duration,
+    /// `choose_package_version` offers no version of the package it picked.
+    pub no_version: f64,
+}
+
+/// How many of each fault a provider has injected so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Injected {
+    pub errors: u64,
+    pub unknown: u64,
+    pub delays: u64,
+    pub no_versions: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("injected fault: could not reach the registry for {package} {version}")]
+pub struct InjectedFault {
+    pub package: PackageName,
+    pub version: Version,
+}
+
+pub struct FaultyDependencyProvider<DP> {
+    inner: DP,
+    faults: Faults,
+    random: RefCell<Random>,
+    injected: Cell<Injected>,
+}
+
+/// One resolution under faults.
+#[derive(Debug)]
+pub struct FaultRun {
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub injected: Injected,
+}
+
+#[derive(Debug)]
+pub struct Robustness {
+    /// The outcome with no faults injected.
+    pub baseline: Outcome,
+    pub runs: Vec<FaultRun>,
+}
+
+impl Default for Faults {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            error: 0.05,
+            unknown: 0.05,
+            delay: 0.0,
+            delay_for: Duration::from_millis(10),
+            no_version: 0.05,
+        }
+    }
+}
+
+impl<DP> FaultyDependencyProvider<DP> {
+    pub fn new(inner: DP, faults: Faults) -> Self {
+        Self {
+            inner,
+            faults,
+            random: RefCell::new(Random::new(faults.seed)),
+            injected: Cell::new(Injected::default()),
+        }
+    }
+
+    pub fn injected(&self) -> Injected {
+        self.injected.get()
+    }
+
+    fn roll(&self, chance: f64, count: impl FnOnce(&mut Injected) -> &mut u64) -> bool {
+        if !self.random.borrow_mut().chance(chance) {
+            return false;
+        }
+        let mut injected = self.injected.get();
+        *count(&mut injected) += 1;
+        self.injected.set(injected);
+        true
+    }
+}
+
+impl<DP> DependencyProvider<PackageName, Version> for FaultyDependencyProvider<DP>
+where
+    DP: DependencyProvider<PackageName, Version>,
+{
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn StdError>> {
+        let (package, version) = self.inner.choose_package_version(potential_packages)?;
+        if version.is_some() && self.roll(self.faults.no_version, |i| &mut i.no_versions) {
+            return Ok((package, None));
+        }
+        Ok((package, version))
+    }
+
+    fn get_dependencies(
+        &self,
+        name: &PackageName,
+        version: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn StdError>> {
+        if self.roll(self.faults.delay, |i| &mut i.delays) {
+            thread::sleep(self.faults.delay_for);
+        }
+        if self.roll(self.faults.error, |i| &mut i.errors) {
+            return Err(Box::new(InjectedFault {
+                package: name.clone(),
+                version: version.clone(),
+            }));
+        }
+        if self.roll(self.faults.unknown, |i| &mut i.unknown) {
+            return Ok(Dependencies::Unknown);
+        }
+        self.inner.get_dependencies(name, version)
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn StdError>> {
+        self.inner.should_cancel()
+    }
+}
+
+/// Resolves `fixture` once without faults, then `runs` times under `faults`
+/// with seeds counting up from its own.
+pub fn check(fixture: &Fixture, faults: Faults, runs: usize, budget: Budget) -> Robustness {
+    let provider = BudgetedDependencyProvider::new(
+        Issue3201DependencyProvider::from_registry(&fixture.registry),
+        budget,
+    );
+    let baseline = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+
+    let runs = (faults.seed..)
+        .take(runs)
+        .map(|seed| {
+            let provider = BudgetedDependencyProvider::new(
+                FaultyDependencyProvider::new(
+                    Issue3201DependencyProvider::from_registry(&fixture.registry),
+                    Faults { seed, ..faults },
+                ),
+                budget,
+            );
+            let outcome = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+            FaultRun {
+                seed,
+                outcome,
+                injected: provider.into_inner().injected(),
+            }
+        })
+        .collect();
+    Robustness { baseline, runs }
+}
+
+impl Robustness {
+    /// Runs that panicked other than the way the fixture panics when nothing
+    /// is injected, if it does.
+    pub fn new_panics(&self) -> impl Iterator<Item = &FaultRun> {
+        let baseline = match &self.baseline {
+            Outcome::Panicked(message) => Some(message),
+            _ => None,
+        };
+        self.runs.iter().filter(move |run| match &run.outcome {
+            Outcome::Panicked(message) => baseline != Some(message),
+            _ => false,
+        })
+    }
+}
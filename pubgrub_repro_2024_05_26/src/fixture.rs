@@ -0,0 +1,264 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use hexpm::version::Version;
+use serde::Deserialize;
+
+use crate::{
+    registry::{Registry, Release, Requirement},
+    PackageName,
+};
+
+/// A registry snapshot and the root package to resolve within it, as stored in
+/// `fixtures/*.toml`.
+///
+/// ```toml
+/// [root]
+/// name = "my_project"
+/// version = "0.0.0"
+///
+/// [packages.my_project."0.0.0".requirements]
+/// gleam_stdlib = ">= 0.34.0 and < 2.0.0"
+///
+/// # Known to have no requirements.
+/// [packages.gleam_stdlib."0.38.0".requirements]
+///
+/// # Listed, but its requirements are unknown.
+/// [packages.wisp."0.14.0"]
+///
+/// # Metadata for manifest.toml is optional.
+/// [packages.thoas."1.2.1"]
+/// build_tools = ["rebar3"]
+/// otp_app = "thoas"
+/// outer_checksum = "E38697EDFFD6E91BD12CEA41B155115282630075C2A727E7A6B2947F5408B86A"
+///
+/// [packages.thoas."1.2.1".requirements]
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub root: PackageName,
+    pub root_version: Version,
+    pub registry: Registry,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FixtureError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid fixture: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("invalid version `{version}` of {package}: {error}")]
+    InvalidVersion {
+        package: PackageName,
+        version: String,
+        error: String,
+    },
+    #[error("invalid requirement `{requirement}` on {dependency} in {package} {version}: {error}")]
+    InvalidRequirement {
+        package: PackageName,
+        version: String,
+        dependency: PackageName,
+        requirement: String,
+        error: String,
+    },
+    #[error("root package {package} {version} is not in the registry")]
+    MissingRoot {
+        package: PackageName,
+        version: Version,
+    },
+}
+
+#[derive(Deserialize)]
+struct RawFixture {
+    root: RawRoot,
+    #[serde(default)]
+    packages: BTreeMap<PackageName, BTreeMap<String, RawRelease>>,
+}
+
+#[derive(Deserialize)]
+struct RawRoot {
+    name: PackageName,
+    #[serde(default = "default_root_version")]
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct RawRelease {
+    requirements: Option<BTreeMap<PackageName, String>>,
+    #[serde(default)]
+    build_tools: Vec<String>,
+    otp_app: Option<String>,
+    outer_checksum: Option<String>,
+}
+
+fn default_root_version() -> String {
+    "0.0.0".into()
+}
+
+impl Fixture {
+    /// The bundled `fixtures/issue_3201.toml`.
+    pub fn issue_3201() -> Self {
+        Self::from_toml(include_str!("../fixtures/issue_3201.toml"))
+            .expect("bundled fixture is valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, FixtureError> {
+        let source = std::fs::read_to_string(path).map_err(|source| FixtureError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::from_toml(&source)
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, FixtureError> {
+        let raw: RawFixture = toml::from_str(source)?;
+
+        let root_version = parse_version(&raw.root.name, &raw.root.version)?;
+
+        let mut registry = Registry::new();
+        for (name, releases) in raw.packages {
+            for (version, raw_release) in releases {
+                let version = parse_version(&name, &version)?;
+                let requirements = match raw_release.requirements {
+                    None => None,
+                    Some(raw_requirements) => {
+                        let mut requirements = BTreeMap::new();
+                        for (dependency, requirement) in raw_requirements {
+                            let parsed = Requirement::parse(&requirement).map_err(|error| {
+                                FixtureError::InvalidRequirement {
+                                    package: name.clone(),
+                                    version: version.to_string(),
+                                    dependency: dependency.clone(),
+                                    requirement: requirement.clone(),
+                                    error: error.to_string(),
+                                }
+                            })?;
+                            let _ = requirements.insert(dependency, parsed);
+                        }
+                        Some(requirements)
+                    }
+                };
+                let release = Release {
+                    requirements,
+                    build_tools: raw_release.build_tools,
+                    otp_app: raw_release.otp_app,
+                    outer_checksum: raw_release.outer_checksum,
+                };
+                registry.insert(name.clone(), version, release);
+            }
+        }
+
+        if registry.release(&raw.root.name, &root_version).is_none() {
+            return Err(FixtureError::MissingRoot {
+                package: raw.root.name,
+                version: root_version,
+            });
+        }
+
+        Ok(Self {
+            root: raw.root.name,
+            root_version,
+            registry,
+        })
+    }
+
+    /// The fixture in the format [`Fixture::from_toml`] reads, root first.
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "[root]");
+        let _ = writeln!(out, "name = {}", quote(&self.root));
+        let _ = writeln!(out, "version = \"{}\"", self.root_version);
+
+        let root = self
+            .registry
+            .releases(&self.root)
+            .map(|releases| (&self.root, releases));
+        let others = self
+            .registry
+            .packages()
+            .filter(|(name, _)| **name != self.root);
+        for (name, releases) in root.into_iter().chain(others) {
+            for (version, release) in releases {
+                let table = format!("packages.{}.\"{version}\"", key(name));
+                let has_metadata = !release.build_tools.is_empty()
+                    || release.otp_app.is_some()
+                    || release.outer_checksum.is_some();
+                if has_metadata || release.requirements.is_none() {
+                    let _ = writeln!(out, "\n[{table}]");
+                }
+                if !release.build_tools.is_empty() {
+                    let tools: Vec<_> = release.build_tools.iter().map(|t| quote(t)).collect();
+                    let _ = writeln!(out, "build_tools = [{}]", tools.join(", "));
+                }
+                if let Some(otp_app) = &release.otp_app {
+                    let _ = writeln!(out, "otp_app = {}", quote(otp_app));
+                }
+                if let Some(checksum) = &release.outer_checksum {
+                    let _ = writeln!(out, "outer_checksum = {}", quote(checksum));
+                }
+                if let Some(requirements) = &release.requirements {
+                    let _ = writeln!(out, "\n[{table}.requirements]");
+                    for (dependency, requirement) in requirements {
+                        let _ = writeln!(
+                            out,
+                            "{} = {}",
+                            key(dependency),
+                            quote(requirement.spec.as_str())
+                        );
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn key(name: &str) -> String {
+    let bare = !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+    if bare {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Every `*.toml` fixture in a directory, in name order.
+pub fn corpus(directory: &Path) -> Result<Vec<PathBuf>, FixtureError> {
+    let io_error = |source| FixtureError::Io {
+        path: directory.display().to_string(),
+        source,
+    };
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(directory).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn parse_version(package: &str, version: &str) -> Result<Version, FixtureError> {
+    Version::parse(version).map_err(|error| FixtureError::InvalidVersion {
+        package: package.to_string(),
+        version: version.to_string(),
+        error: error.to_string(),
+    })
+}
@@ -0,0 +1,261 @@
+//! Dependency graphs as Graphviz DOT or Mermaid, for registries too big to
+//! follow as a Rust literal.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use hexpm::version::Version;
+
+use crate::{diff::Resolution, enumerate::Solution, registry::Registry, PackageName};
+
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// `None` for the root of a resolution.
+    pub package: Option<PackageName>,
+    /// `None` when no release of the package is in the graph.
+    pub version: Option<Version>,
+    /// Part of the resolution.
+    pub chosen: bool,
+}
+
+/// A requirement, from the requiring node to a node it admits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    /// Empty when the range is unknown.
+    pub requirement: String,
+    /// The requirement admits no release in the registry, or not the one
+    /// chosen.
+    pub conflict: bool,
+}
+
+/// Every release, with an edge from each to every release of each dependency
+/// its requirement admits. Releases in `solution` are marked chosen.
+pub fn registry(registry: &Registry, solution: Option<&Solution>) -> Graph {
+    let mut graph = Graph::default();
+    let mut ids = BTreeMap::new();
+    for (name, releases) in registry.packages() {
+        for version in releases.keys() {
+            let chosen = solution.is_some_and(|solution| solution.get(name) == Some(version));
+            let id = graph.node(Some(name), Some(version), chosen);
+            let _ = ids.insert((name.as_str(), version), id);
+        }
+    }
+
+    for (name, releases) in registry.packages() {
+        for (version, release) in releases {
+            let from = ids[&(name.as_str(), version)];
+            for (dependency, requirement) in release.requirements.iter().flatten() {
+                let spec = requirement.spec.as_str();
+                let admitted: Vec<_> = registry
+                    .releases(dependency)
+                    .into_iter()
+                    .flat_map(|releases| releases.keys())
+                    .filter(|version| requirement.range.contains(version))
+                    .collect();
+                if admitted.is_empty() {
+                    let to = graph.node(Some(dependency), None, false);
+                    graph.edge(from, to, spec, true);
+                }
+                for version in admitted {
+                    graph.edge(from, ids[&(dependency.as_str(), version)], spec, false);
+                }
+            }
+        }
+    }
+    graph
+}
+
+/// Only the chosen releases and the requirements between them.
+pub fn resolution(resolution: &Resolution) -> Graph {
+    let mut graph = Graph::default();
+    let root = graph.node(None, None, true);
+    let mut ids = BTreeMap::new();
+    for (name, version) in &resolution.versions {
+        let _ = ids.insert(name.as_str(), graph.node(Some(name), Some(version), true));
+    }
+
+    for (dependency, edges) in &resolution.dependents {
+        let to = match ids.get(dependency.as_str()) {
+            Some(&to) => to,
+            None => graph.node(Some(dependency), None, false),
+        };
+        let chosen = resolution.versions.get(dependency);
+        for edge in edges {
+            let from = match &edge.from {
+                Some((name, version)) => match ids.get(name.as_str()) {
+                    Some(&from) => from,
+                    None => graph.node(Some(name), Some(version), true),
+                },
+                None => root,
+            };
+            let conflict = match (&edge.requirement, chosen) {
+                (_, None) => true,
+                (Some(requirement), Some(version)) => !requirement.range.contains(version),
+                (None, Some(_)) => false,
+            };
+            let spec = edge
+                .requirement
+                .as_ref()
+                .map_or("", |requirement| requirement.spec.as_str());
+            graph.edge(from, to, spec, conflict);
+        }
+    }
+    graph
+}
+
+impl Graph {
+    fn node(&mut self, package: Option<&str>, version: Option<&Version>, chosen: bool) -> usize {
+        let node = Node {
+            package: package.map(str::to_string),
+            version: version.cloned(),
+            chosen,
+        };
+        if let Some(existing) = self.nodes.iter().position(|other| *other == node) {
+            return existing;
+        }
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn edge(&mut self, from: usize, to: usize, requirement: &str, conflict: bool) {
+        self.edges.push(Edge {
+            from,
+            to,
+            requirement: requirement.to_string(),
+            conflict,
+        });
+    }
+
+    /// Releases of the same package are grouped in a cluster, chosen ones are
+    /// bold and conflicting edges red.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph dependencies {{");
+        let _ = writeln!(out, "  rankdir=LR;");
+        let _ = writeln!(out, "  node [shape=box];");
+
+        let mut packages: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (id, node) in self.nodes.iter().enumerate() {
+            packages.entry(&node.package).or_default().push(id);
+        }
+        for (package, ids) in packages {
+            let indent = match package {
+                Some(package) if ids.len() > 1 => {
+                    let _ = writeln!(out, "  subgraph \"cluster_{}\" {{", dot_escape(package));
+                    let _ = writeln!(out, "    label=\"{}\";", dot_escape(package));
+                    "    "
+                }
+                _ => "  ",
+            };
+            for id in &ids {
+                let node = &self.nodes[*id];
+                let mut attributes = format!("label=\"{}\"", dot_escape(&node.label()));
+                if node.chosen {
+                    attributes.push_str(", style=bold");
+                }
+                if node.version.is_none() && node.package.is_some() {
+                    attributes.push_str(", style=dashed, color=red");
+                }
+                let _ = writeln!(out, "{indent}n{id} [{attributes}];");
+            }
+            if indent.len() > 2 {
+                let _ = writeln!(out, "  }}");
+            }
+        }
+
+        for edge in &self.edges {
+            let mut attributes = format!("label=\"{}\"", dot_escape(&edge.requirement));
+            if edge.conflict {
+                attributes.push_str(", color=red, fontcolor=red, penwidth=2");
+            }
+            let _ = writeln!(out, "  n{} -> n{} [{attributes}];", edge.from, edge.to);
+        }
+        let _ = writeln!(out, "}}");
+        out
+    }
+
+    /// A flowchart with chosen releases bold and conflicting edges red.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "graph LR");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(out, "  n{id}[\"{}\"]", mermaid_escape(&node.label()));
+        }
+        for edge in &self.edges {
+            if edge.requirement.is_empty() {
+                let _ = writeln!(out, "  n{} --> n{}", edge.from, edge.to);
+            } else {
+                let _ = writeln!(
+                    out,
+                    "  n{} -->|\"{}\"| n{}",
+                    edge.from,
+                    mermaid_escape(&edge.requirement),
+                    edge.to
+                );
+            }
+        }
+
+        let chosen: Vec<_> = self.ids(|node| node.chosen);
+        if !chosen.is_empty() {
+            let _ = writeln!(out, "  classDef chosen font-weight:bold,stroke-width:3px");
+            let _ = writeln!(out, "  class {} chosen", chosen.join(","));
+        }
+        let missing: Vec<_> = self.ids(|node| node.version.is_none() && node.package.is_some());
+        if !missing.is_empty() {
+            let _ = writeln!(out, "  classDef missing stroke:red,stroke-dasharray:5");
+            let _ = writeln!(out, "  class {} missing", missing.join(","));
+        }
+        let conflicts: Vec<_> = self
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| edge.conflict)
+            .map(|(index, _)| index.to_string())
+            .collect();
+        if !conflicts.is_empty() {
+            let _ = writeln!(
+                out,
+                "  linkStyle {} stroke:red,stroke-width:2px,color:red",
+                conflicts.join(",")
+            );
+        }
+        out
+    }
+
+    fn ids(&self, matches: impl Fn(&Node) -> bool) -> Vec<String> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| matches(node))
+            .map(|(id, _)| format!("n{id}"))
+            .collect()
+    }
+}
+
+impl Node {
+    /// "name version", or "root" for the root of a resolution.
+    pub fn label(&self) -> String {
+        match (&self.package, &self.version) {
+            (Some(package), Some(version)) => format!("{package} {version}"),
+            (Some(package), None) => format!("{package} (none)"),
+            (None, _) => "root".into(),
+        }
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
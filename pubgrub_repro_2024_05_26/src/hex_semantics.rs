@@ -0,0 +1,298 @@
+//! A direct reading of Elixir's `Version.match?/3`, which is what Hex
+//! requirements mean, to check `hexpm::version::Range::to_pubgrub` against.
+//!
+//! The conversion has to express every requirement as a pubgrub range, and the
+//! edges of those ranges are where pre-releases and build metadata can fall
+//! through the cracks: `~> 1.0` is `< 2.0.0-0` in Elixir but `< 2.0.0` once
+//! converted, so `2.0.0-rc1` matches one and not the other.
+
+use std::{cmp::Ordering, collections::BTreeSet, fmt};
+
+use hexpm::version::{Identifier, Range, Version};
+
+/// A requirement parsed into clauses, `or` of `and`s, matched the way Elixir
+/// does rather than through a pubgrub range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexRequirement {
+    alternatives: Vec<Vec<Clause>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Clause {
+    operator: Operator,
+    operand: Version,
+    /// Only for `~>`, the exclusive upper bound.
+    upper: Option<Version>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    NotEq,
+    Gt,
+    GtEq,
+    Lt,
+    LtEq,
+    Pessimistic,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Operator(Operator),
+    And,
+    Or,
+    Version(String),
+}
+
+/// Whether a version matches under each semantics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub version: Version,
+    pub hex: bool,
+    pub pubgrub: bool,
+}
+
+impl HexRequirement {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut tokens = tokenise(spec)?.into_iter().peekable();
+        let mut alternatives = vec![];
+        let mut clauses = vec![];
+        loop {
+            let operator = match tokens.peek() {
+                Some(Token::Operator(operator)) => {
+                    let operator = *operator;
+                    let _ = tokens.next();
+                    operator
+                }
+                _ => Operator::Eq,
+            };
+            let Some(Token::Version(operand)) = tokens.next() else {
+                return Err(format!("expected a version in `{spec}`"));
+            };
+            clauses.push(Clause::new(operator, &operand)?);
+
+            match tokens.next() {
+                None => break,
+                Some(Token::And) => {}
+                Some(Token::Or) => alternatives.push(std::mem::take(&mut clauses)),
+                Some(token) => return Err(format!("unexpected {token} in `{spec}`")),
+            }
+        }
+        alternatives.push(clauses);
+        Ok(Self { alternatives })
+    }
+
+    /// `allow_pre` is the option of the same name to `Version.match?/3`: when
+    /// false a pre-release only matches clauses whose operand is itself a
+    /// pre-release. Elixir defaults it to true.
+    pub fn matches(&self, version: &Version, allow_pre: bool) -> bool {
+        self.alternatives.iter().any(|clauses| {
+            clauses
+                .iter()
+                .all(|clause| clause.matches(version, allow_pre))
+        })
+    }
+
+    /// Versions either side of every bound in the requirement, which is where
+    /// the two semantics can disagree.
+    pub fn probe_versions(&self) -> Vec<Version> {
+        let mut probes = BTreeSet::new();
+        for clause in self.alternatives.iter().flatten() {
+            for bound in std::iter::once(&clause.operand).chain(clause.upper.as_ref()) {
+                let release = release_of(bound);
+                let next_patch = Version::new(release.major, release.minor, release.patch + 1);
+                // Between this pre-release and the next one, `1.0.0-rc2.1`.
+                if bound.is_pre() {
+                    let mut pre = bound.pre.clone();
+                    pre.push(Identifier::Numeric(1));
+                    let _ = probes.insert(ByPrecedence(with_pre(bound, pre)));
+                }
+                for version in [
+                    with_pre(&release, vec![Identifier::Numeric(0)]),
+                    with_pre(&release, vec![Identifier::AlphaNumeric("rc1".into())]),
+                    bound.clone(),
+                    with_build(&release, "build"),
+                    release,
+                    with_pre(&next_patch, vec![Identifier::AlphaNumeric("rc1".into())]),
+                    next_patch,
+                ] {
+                    let _ = probes.insert(ByPrecedence(version));
+                }
+            }
+        }
+        probes
+            .into_iter()
+            .map(|ByPrecedence(version)| version)
+            .collect()
+    }
+}
+
+impl Clause {
+    fn new(operator: Operator, operand: &str) -> Result<Self, String> {
+        if operator != Operator::Pessimistic {
+            let operand = Version::parse(operand).map_err(|error| error.to_string())?;
+            return Ok(Self {
+                operator,
+                operand,
+                upper: None,
+            });
+        }
+
+        // `~>` takes `major.minor` or `major.minor.patch`, and bumps whichever
+        // component is second to last. Elixir's upper bound is the `-0`
+        // pre-release of the bump, so no pre-release of it matches either.
+        let release = operand.split(['-', '+']).next().unwrap_or_default();
+        let components = release.split('.').count();
+        let (lower, upper) = match components {
+            2 => {
+                let lower = Version::parse(&operand.replacen(release, &format!("{release}.0"), 1))
+                    .map_err(|error| error.to_string())?;
+                let upper = Version::new(lower.major + 1, 0, 0);
+                (lower, upper)
+            }
+            3 => {
+                let lower = Version::parse(operand).map_err(|error| error.to_string())?;
+                let upper = Version::new(lower.major, lower.minor + 1, 0);
+                (lower, upper)
+            }
+            _ => return Err(format!("`~> {operand}` needs two or three components")),
+        };
+        Ok(Self {
+            operator,
+            operand: lower,
+            upper: Some(with_pre(&upper, vec![Identifier::Numeric(0)])),
+        })
+    }
+
+    fn matches(&self, version: &Version, allow_pre: bool) -> bool {
+        if !allow_pre && version.is_pre() && !self.operand.is_pre() {
+            return false;
+        }
+        let ordering = version.cmp(&self.operand);
+        match self.operator {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::NotEq => ordering != Ordering::Equal,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::GtEq => ordering != Ordering::Less,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::LtEq => ordering != Ordering::Greater,
+            Operator::Pessimistic => {
+                let upper = self.upper.as_ref().expect("`~>` always has an upper bound");
+                ordering != Ordering::Less && version < upper
+            }
+        }
+    }
+}
+
+/// Match each version against a requirement under Hex's semantics and under
+/// the pubgrub range hexpm converts it to.
+pub fn compare(
+    spec: &str,
+    versions: &[Version],
+    allow_pre: bool,
+) -> Result<Vec<Comparison>, String> {
+    let requirement = HexRequirement::parse(spec)?;
+    let range = Range::new(spec.to_string())
+        .to_pubgrub()
+        .map_err(|error| format!("hexpm cannot convert `{spec}`: {error}"))?;
+    Ok(versions
+        .iter()
+        .map(|version| Comparison {
+            version: version.clone(),
+            hex: requirement.matches(version, allow_pre),
+            pubgrub: range.contains(version),
+        })
+        .collect())
+}
+
+impl Comparison {
+    pub fn agrees(&self) -> bool {
+        self.hex == self.pubgrub
+    }
+}
+
+fn tokenise(spec: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut rest = spec.trim_start();
+    while !rest.is_empty() {
+        let operator = [
+            ("~>", Operator::Pessimistic),
+            ("==", Operator::Eq),
+            ("!=", Operator::NotEq),
+            (">=", Operator::GtEq),
+            ("<=", Operator::LtEq),
+            (">", Operator::Gt),
+            ("<", Operator::Lt),
+        ]
+        .into_iter()
+        .find(|(symbol, _)| rest.starts_with(symbol));
+
+        if let Some((symbol, operator)) = operator {
+            tokens.push(Token::Operator(operator));
+            rest = &rest[symbol.len()..];
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')))
+                .unwrap_or(rest.len());
+            if end == 0 {
+                return Err(format!("unexpected `{}` in `{spec}`", &rest[..1]));
+            }
+            tokens.push(match &rest[..end] {
+                "and" => Token::And,
+                "or" => Token::Or,
+                version => Token::Version(version.to_string()),
+            });
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+fn release_of(version: &Version) -> Version {
+    Version::new(version.major, version.minor, version.patch)
+}
+
+fn with_pre(version: &Version, pre: Vec<Identifier>) -> Version {
+    Version {
+        pre,
+        ..release_of(version)
+    }
+}
+
+fn with_build(version: &Version, build: &str) -> Version {
+    Version {
+        build: Some(build.into()),
+        ..version.clone()
+    }
+}
+
+// `Version`'s `Eq` looks at build metadata but its `Ord` does not, so keep
+// `1.0.0` and `1.0.0+build` apart while still sorting by precedence.
+#[derive(PartialEq, Eq)]
+struct ByPrecedence(Version);
+
+impl PartialOrd for ByPrecedence {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByPrecedence {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .cmp(&other.0)
+            .then_with(|| self.0.build.cmp(&other.0.build))
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Operator(_) => write!(f, "operator"),
+            Token::And => write!(f, "`and`"),
+            Token::Or => write!(f, "`or`"),
+            Token::Version(version) => write!(f, "`{version}`"),
+        }
+    }
+}
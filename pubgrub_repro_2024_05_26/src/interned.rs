@@ -0,0 +1,127 @@
+use std::{borrow::Borrow, collections::HashMap, error::Error as StdError};
+
+use hexpm::version::Version;
+use pubgrub::solver::Dependencies;
+
+use crate::{registry::Registry, PackageName, PubgrubRange};
+
+/// A drop-in replacement for [`Issue3201DependencyProvider`] meant for large
+/// registries.
+///
+/// Package names are interned once up front and every lookup goes through a
+/// `&str` keyed map into pre-sorted slices, so choosing a version and finding
+/// dependencies never allocate. The only copies made are the ones pubgrub's
+/// API requires: the chosen version and the returned dependency map.
+///
+/// [`Issue3201DependencyProvider`]: crate::issue_3201::Issue3201DependencyProvider
+pub struct InternedDependencyProvider {
+    ids: HashMap<PackageName, PackageId>,
+    packages: Vec<InternedPackage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackageId(u32);
+
+struct InternedPackage {
+    name: PackageName,
+    /// Newest first, the order versions are preferred in.
+    versions: Vec<Version>,
+    /// Indexed in step with `versions`, `None` where they are unknown.
+    dependencies: Vec<Option<Dependencies<PackageName, Version>>>,
+}
+
+impl InternedDependencyProvider {
+    pub fn from_registry(registry: &Registry) -> Self {
+        let mut this = Self {
+            ids: HashMap::with_capacity(registry.package_count()),
+            packages: Vec::with_capacity(registry.package_count()),
+        };
+
+        for (name, releases) in registry.packages() {
+            let id = PackageId(this.packages.len() as u32);
+            let _ = this.ids.insert(name.clone(), id);
+            this.packages.push(InternedPackage {
+                name: name.clone(),
+                versions: releases.keys().rev().cloned().collect(),
+                dependencies: releases
+                    .values()
+                    .rev()
+                    .map(|release| release.dependency_constraints().map(Dependencies::Known))
+                    .collect(),
+            });
+        }
+
+        this
+    }
+
+    pub fn package_id(&self, name: &str) -> Option<PackageId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn package_name(&self, id: PackageId) -> &str {
+        &self.packages[id.0 as usize].name
+    }
+
+    /// Available versions of a package, newest first. Unknown packages have
+    /// none.
+    pub fn versions(&self, name: &str) -> &[Version] {
+        match self.package(name) {
+            Some(package) => &package.versions,
+            None => &[],
+        }
+    }
+
+    fn package(&self, name: &str) -> Option<&InternedPackage> {
+        let id = self.package_id(name)?;
+        Some(&self.packages[id.0 as usize])
+    }
+}
+
+impl pubgrub::solver::DependencyProvider<PackageName, Version> for InternedDependencyProvider {
+    // This is `choose_package_with_fewest_versions` without the per-candidate
+    // `Vec<Version>` it needs: count matches straight off the sorted slice and
+    // only clone the version that is finally picked. Ties go to the first
+    // package offered, same as the helper.
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn StdError>> {
+        let mut fewest: Option<(Name, Ver, usize)> = None;
+        for (name, range) in potential_packages {
+            let count = self
+                .versions(name.borrow())
+                .iter()
+                .filter(|version| range.borrow().contains(version))
+                .count();
+            if fewest.as_ref().is_none_or(|(_, _, fewest)| count < *fewest) {
+                fewest = Some((name, range, count));
+            }
+        }
+
+        let Some((name, range, _)) = fewest else {
+            return Err("potential_packages gave us an empty iterator".into());
+        };
+        let version = self
+            .versions(name.borrow())
+            .iter()
+            .find(|version| range.borrow().contains(version))
+            .cloned();
+        Ok((name, version))
+    }
+
+    fn get_dependencies(
+        &self,
+        name: &PackageName,
+        version: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn StdError>> {
+        self.package(name)
+            .and_then(|package| {
+                let index = package
+                    .versions
+                    .binary_search_by(|candidate| version.cmp(candidate))
+                    .ok()?;
+                package.dependencies[index].clone()
+            })
+            .ok_or_else(|| "failed to get dependencies".into())
+    }
+}
@@ -0,0 +1,377 @@
+//! A Markdown bug report for pubgrub, ready to file, from a fixture it fails
+//! on: the registry shrunk to what still fails, as a fixture and as a
+//! standalone provider in the style of `Issue3201DependencyProvider`, the
+//! output, and the versions and environment involved.
+
+use std::{fmt::Write, process::Command};
+
+use hexpm::version::Version;
+
+use crate::{
+    budget::{Budget, BudgetedDependencyProvider},
+    enumerate::Solution,
+    fixture::Fixture,
+    issue_3201::Issue3201DependencyProvider,
+    minimise::minimise,
+    outcome::{self, Outcome},
+    registry::Release,
+    sat::{self, SatError},
+};
+
+/// How pubgrub gets a fixture wrong. A minimised fixture must fail the same
+/// way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    Panicked(String),
+    Failed(String),
+    /// pubgrub reports no solution, but the SAT backend finds this one.
+    MissedSolution(Solution),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IssueError {
+    #[error("there is nothing to report, the fixture resolves")]
+    Solved,
+    #[error("there is no solution, and the SAT backend agrees, so pubgrub is right")]
+    NoSolution,
+    #[error("resolution ran out of budget: {0}")]
+    TimedOut(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub original: Fixture,
+    /// The same as `original` when minimising was skipped.
+    pub minimised: Fixture,
+    pub failure: Failure,
+    /// Where pubgrub panicked, as the panic hook reported it on this thread.
+    pub panic_location: Option<String>,
+}
+
+/// Every resolution made, including those while minimising, is limited to
+/// this many steps.
+const MAX_STEPS: u64 = 100_000;
+
+/// Checks that pubgrub fails on `fixture`, then shrinks it if
+/// `minimise_fixture` is set. Panics are not printed while this runs.
+pub fn prepare(fixture: &Fixture, minimise_fixture: bool) -> Result<Issue, IssueError> {
+    outcome::quietly(|| {
+        let failure = diagnose(fixture)?;
+        // Minimising panics again, so take the location first.
+        let panic_location = match failure {
+            Failure::Panicked(_) => outcome::panic_location(),
+            _ => None,
+        };
+        let (minimised, failure) = if minimise_fixture {
+            let minimised = minimise(fixture, |candidate| {
+                diagnose(candidate).is_ok_and(|candidate| candidate.is_like(&failure))
+            });
+            // A missed solution must be one of the minimised registry.
+            let failure = diagnose(&minimised).unwrap_or(failure);
+            (minimised, failure)
+        } else {
+            (fixture.clone(), failure)
+        };
+        Ok(Issue {
+            original: fixture.clone(),
+            minimised,
+            failure,
+            panic_location,
+        })
+    })
+}
+
+fn diagnose(fixture: &Fixture) -> Result<Failure, IssueError> {
+    let budget = Budget {
+        max_steps: Some(MAX_STEPS),
+        ..Budget::default()
+    };
+    let provider = BudgetedDependencyProvider::new(
+        Issue3201DependencyProvider::from_registry(&fixture.registry),
+        budget,
+    );
+    match outcome::resolve(&provider, &fixture.root, &fixture.root_version) {
+        Outcome::Solved(_) => Err(IssueError::Solved),
+        Outcome::Panicked(message) => Ok(Failure::Panicked(message)),
+        Outcome::Failed(message) => Ok(Failure::Failed(message)),
+        Outcome::NoSolution(_) | Outcome::Unsatisfiable => {
+            match sat::resolve(
+                &fixture.registry,
+                &fixture.root,
+                &fixture.root_version,
+                budget,
+            ) {
+                Ok(solution) => Ok(Failure::MissedSolution(solution.into_iter().collect())),
+                Err(SatError::NoSolution) => Err(IssueError::NoSolution),
+                Err(SatError::BudgetExceeded(exceeded)) => {
+                    Err(IssueError::TimedOut(exceeded.to_string()))
+                }
+            }
+        }
+        Outcome::TimedOut(exceeded) => Err(IssueError::TimedOut(exceeded.to_string())),
+    }
+}
+
+impl Issue {
+    pub fn markdown(&self) -> String {
+        let fixture = &self.minimised;
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title());
+
+        let _ = writeln!(
+            out,
+            "Resolving `{} {}` with pubgrub {} {}.\n",
+            fixture.root,
+            fixture.root_version,
+            locked_version("pubgrub").unwrap_or("(unknown version)"),
+            match &self.failure {
+                Failure::Panicked(_) => "panics",
+                Failure::Failed(_) => "fails with an error",
+                Failure::MissedSolution(_) => "reports no solution, although there is one",
+            }
+        );
+        if fixture.registry.release_count() < self.original.registry.release_count() {
+            let _ = writeln!(
+                out,
+                "The registry has been minimised from {} packages and {} releases to {} packages and {} releases that still fail the same way.\n",
+                self.original.registry.package_count(),
+                self.original.registry.release_count(),
+                fixture.registry.package_count(),
+                fixture.registry.release_count(),
+            );
+        }
+
+        let _ = writeln!(out, "## Reproduction\n");
+        let _ = writeln!(
+            out,
+            "1. Create a crate depending on `pubgrub = \"={}\"` and `hexpm = \"={}\"`.",
+            locked_version("pubgrub").unwrap_or("*"),
+            locked_version("hexpm").unwrap_or("*"),
+        );
+        let _ = writeln!(out, "2. Replace its `src/main.rs` with the code below.");
+        let _ = writeln!(out, "3. `cargo run`.\n");
+        let _ = writeln!(out, "```rust\n{}```\n", rust(fixture));
+        let _ = writeln!(
+            out,
+            "The same registry as a fixture for pubgrub_repro_2024_05_26, to run with `cargo run -- repro.toml`:\n"
+        );
+        let _ = writeln!(out, "```toml\n{}```\n", fixture.to_toml());
+
+        let _ = writeln!(out, "## Output\n");
+        let _ = writeln!(out, "```");
+        match &self.failure {
+            Failure::Panicked(message) => {
+                match &self.panic_location {
+                    Some(location) => {
+                        let _ = writeln!(out, "thread 'main' panicked at {location}:");
+                    }
+                    None => {
+                        let _ = writeln!(out, "thread 'main' panicked:");
+                    }
+                }
+                let _ = writeln!(out, "{message}");
+            }
+            Failure::Failed(message) => {
+                let _ = writeln!(out, "{message}");
+            }
+            Failure::MissedSolution(_) => {
+                let _ = writeln!(out, "Err(NoSolution(..))");
+            }
+        }
+        let _ = writeln!(out, "```\n");
+        if let Failure::MissedSolution(solution) = &self.failure {
+            let _ = writeln!(out, "Yet this is a solution:\n");
+            for (name, version) in solution {
+                let _ = writeln!(out, "- {name} {version}");
+            }
+            let _ = writeln!(out);
+        }
+
+        let _ = writeln!(out, "## Versions\n");
+        let _ = writeln!(out, "| Crate | Version |\n| --- | --- |");
+        for name in ["pubgrub", "hexpm"] {
+            let _ = writeln!(
+                out,
+                "| {name} | {} |",
+                locked_version(name).unwrap_or("unknown")
+            );
+        }
+        let _ = writeln!(out, "\n## Environment\n");
+        let _ = writeln!(
+            out,
+            "- OS: {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+        let _ = writeln!(out, "- Rust: {}", rustc_version());
+        let _ = writeln!(
+            out,
+            "- Generated by pubgrub_repro_2024_05_26 {}",
+            env!("CARGO_PKG_VERSION")
+        );
+        out
+    }
+
+    fn title(&self) -> String {
+        match &self.failure {
+            Failure::Panicked(message) => format!("Panic: {message}"),
+            Failure::Failed(message) => format!("Resolution fails: {message}"),
+            Failure::MissedSolution(_) => "No solution reported for a solvable registry".into(),
+        }
+    }
+}
+
+impl Failure {
+    /// Whether a minimised fixture still fails like this. A missed solution
+    /// may be a different one once releases are gone.
+    fn is_like(&self, other: &Failure) -> bool {
+        match (self, other) {
+            (Failure::MissedSolution(_), Failure::MissedSolution(_)) => true,
+            _ => self == other,
+        }
+    }
+}
+
+/// A standalone program building the fixture's registry the way
+/// `Issue3201DependencyProvider::new` does, and resolving it.
+pub fn rust(fixture: &Fixture) -> String {
+    let mut out = String::from(RUST_PRELUDE);
+    let _ = writeln!(out, "impl ReproDependencyProvider {{");
+    let _ = writeln!(out, "    fn new() -> Self {{");
+    let _ = writeln!(out, "        let mut this = Self {{");
+    let _ = writeln!(out, "            available_versions: HashMap::default(),");
+    let _ = writeln!(out, "            dependencies: HashMap::default(),");
+    let _ = writeln!(out, "        }};\n");
+
+    for (name, releases) in fixture.registry.packages() {
+        for version in releases.keys().rev() {
+            let _ = writeln!(out, "        this.available_versions");
+            let _ = writeln!(out, "            .entry({name:?}.to_string())");
+            let _ = writeln!(out, "            .or_default()");
+            let _ = writeln!(
+                out,
+                "            .push(Version::parse(\"{version}\").unwrap());"
+            );
+        }
+    }
+
+    for (name, releases) in fixture.registry.packages() {
+        for (version, release) in releases.iter().rev() {
+            dependencies(&mut out, name, version, release);
+        }
+    }
+
+    let _ = writeln!(out, "\n        this\n    }}\n}}\n");
+    let _ = writeln!(out, "fn main() {{");
+    let _ = writeln!(out, "    let provider = ReproDependencyProvider::new();");
+    let _ = writeln!(out, "    let result = resolve(");
+    let _ = writeln!(out, "        &provider,");
+    let _ = writeln!(out, "        {:?}.to_string(),", fixture.root);
+    let _ = writeln!(
+        out,
+        "        Version::parse(\"{}\").unwrap(),",
+        fixture.root_version
+    );
+    let _ = writeln!(out, "    );");
+    let _ = writeln!(out, "    println!(\"{{result:?}}\");");
+    let _ = writeln!(out, "}}");
+    out
+}
+
+// Releases with unknown requirements get no entry, so `get_dependencies`
+// fails for them.
+fn dependencies(out: &mut String, name: &str, version: &Version, release: &Release) {
+    let Some(requirements) = &release.requirements else {
+        return;
+    };
+    let _ = writeln!(out, "        let _ = this.dependencies.insert(");
+    let _ = writeln!(
+        out,
+        "            ({name:?}.to_string(), Version::parse(\"{version}\").unwrap()),"
+    );
+    if requirements.is_empty() {
+        let _ = writeln!(out, "            Dependencies::Known(Map::from_iter([])),");
+        let _ = writeln!(out, "        );");
+        return;
+    }
+    let _ = writeln!(out, "            Dependencies::Known(Map::from_iter([");
+    for (dependency, requirement) in requirements {
+        let range = match requirement.spec.as_str() {
+            "*" => "pubgrub::range::Range::any()".to_string(),
+            spec => format!("Range::new({spec:?}.to_string()).to_pubgrub().unwrap()"),
+        };
+        let _ = writeln!(
+            out,
+            "                ({dependency:?}.to_string(), {range}),"
+        );
+    }
+    let _ = writeln!(out, "            ])),");
+    let _ = writeln!(out, "        );");
+}
+
+const RUST_PRELUDE: &str = r#"use std::{borrow::Borrow, collections::HashMap, error::Error};
+
+use hexpm::version::{Range, Version};
+use pubgrub::{
+    solver::{choose_package_with_fewest_versions, resolve, Dependencies, DependencyProvider},
+    type_aliases::Map,
+};
+
+type PackageName = String;
+type PubgrubRange = pubgrub::range::Range<Version>;
+
+struct ReproDependencyProvider {
+    available_versions: HashMap<PackageName, Vec<Version>>,
+    dependencies: HashMap<(PackageName, Version), Dependencies<PackageName, Version>>,
+}
+
+impl DependencyProvider<PackageName, Version> for ReproDependencyProvider {
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn Error>> {
+        Ok(choose_package_with_fewest_versions(
+            |name: &String| {
+                let Some(available_versions) = self.available_versions.get(name) else {
+                    return Vec::new().into_iter();
+                };
+
+                available_versions.clone().into_iter()
+            },
+            potential_packages,
+        ))
+    }
+
+    fn get_dependencies(
+        &self,
+        name: &PackageName,
+        version: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn Error>> {
+        self.dependencies
+            .get(&(name.clone(), version.clone()))
+            .cloned()
+            .ok_or_else(|| "failed to get dependencies".into())
+    }
+}
+
+"#;
+
+/// The version of a dependency this crate was built with, from Cargo.lock.
+pub fn locked_version(name: &str) -> Option<&'static str> {
+    let lock = include_str!("../Cargo.lock");
+    let marker = format!("name = \"{name}\"\nversion = \"");
+    let start = lock.find(&marker)? + marker.len();
+    let length = lock[start..].find('"')?;
+    Some(&lock[start..start + length])
+}
+
+fn rustc_version() -> String {
+    Command::new("rustc")
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .filter(|version| !version.is_empty())
+        .unwrap_or_else(|| "unknown".into())
+}
@@ -0,0 +1,36 @@
+pub mod budget;
+pub mod cnf;
+pub mod derivation;
+pub mod determinism;
+pub mod diff;
+pub mod enumerate;
+pub mod faults;
+pub mod fixture;
+pub mod graph;
+pub mod hex_semantics;
+pub mod interned;
+pub mod issue_3201;
+pub mod issue_report;
+pub mod manifest;
+pub mod minimise;
+pub mod optimal;
+pub mod outcome;
+pub mod project;
+pub mod random;
+pub mod registry;
+pub mod relax;
+pub mod replay;
+pub mod report;
+pub mod sat;
+pub mod scenario;
+pub mod snapshot;
+pub mod stats;
+pub mod trace;
+pub mod why;
+pub mod why_not;
+
+use hexpm::version::Version;
+
+pub type PackageName = String;
+
+pub type PubgrubRange = pubgrub::range::Range<Version>;
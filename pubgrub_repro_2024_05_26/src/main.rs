@@ -1,601 +1,1430 @@
-use std::{borrow::Borrow, collections::HashMap, error::Error as StdError};
+use std::{
+    collections::BTreeMap,
+    error::Error as StdError,
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
 
-use hexpm::version::{Range, Version};
-use pubgrub::{
-    solver::{choose_package_with_fewest_versions, Dependencies},
-    type_aliases::Map,
+use clap::{Parser, Subcommand, ValueEnum};
+use hexpm::version::Version;
+use pubgrub::solver::DependencyProvider;
+use pubgrub_repro_2024_05_26::{
+    budget::{Budget, BudgetedDependencyProvider},
+    cnf,
+    derivation::DerivationGraph,
+    determinism,
+    diff::{self, Resolution},
+    enumerate,
+    faults::{self, Faults},
+    fixture::{self, Fixture},
+    graph,
+    hex_semantics::{self, Comparison, HexRequirement},
+    interned::InternedDependencyProvider,
+    issue_3201::Issue3201DependencyProvider,
+    issue_report,
+    manifest::Manifest,
+    optimal,
+    outcome::{self, Outcome},
+    project::Project,
+    registry::Registry,
+    relax,
+    replay::{self, RecordingDependencyProvider, ReplayDependencyProvider},
+    report, sat, scenario,
+    snapshot::{self, Status},
+    stats::{Stats, StatsDependencyProvider},
+    trace::{self, TraceFormat, TracingDependencyProvider},
+    why, why_not, PackageName,
 };
 
-fn main() {
-    let dependency_provider = Issue3201DependencyProvider::new();
+#[derive(Parser)]
+#[command(
+    about = "Reproductions of pubgrub problems seen in the Gleam compiler",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    run: RunArgs,
+}
 
-    let result = pubgrub::solver::resolve(
-        &dependency_provider,
-        "gleam_add_issue_2024_05_26".into(),
-        Version::new(0, 0, 0),
-    );
+#[derive(Subcommand)]
+enum Command {
+    /// Resolve a fixture and print the outcome. This is the default.
+    Run(RunArgs),
+    /// Time resolving fixtures with each provider.
+    Bench(BenchArgs),
+    /// Resolve every fixture in a directory, one line per fixture.
+    Corpus(CorpusArgs),
+    /// Show which versions a requirement matches under Hex's semantics and
+    /// as the pubgrub range hexpm converts it to.
+    CheckRequirement(CheckRequirementArgs),
+    /// Resolve a gleam.toml's dependencies against a fixture's registry.
+    Project(ProjectArgs),
+    /// Show which packages moved between two manifests or fixtures, and the
+    /// requirement that forced each move.
+    Diff(DiffArgs),
+    /// Show every path from the root to a package, and the tightest
+    /// requirement on it.
+    Why(WhyArgs),
+    /// Show the packages a fixture resolves below their newest release, and
+    /// what holds each one back.
+    WhyNot(WhyNotArgs),
+    /// Suggest the fewest requirements to loosen to make an unsolvable
+    /// fixture solvable.
+    Relax(RelaxArgs),
+    /// List or count every valid solution by brute force, and check pubgrub's
+    /// answer against them.
+    Solutions(SolutionsArgs),
+    /// Find the newest solution by brute force and list the packages pubgrub
+    /// resolves older than it.
+    Optimal(OptimalArgs),
+    /// Encode a fixture as DIMACS CNF for an external SAT solver, or decode
+    /// the solver's model.
+    Cnf(CnfArgs),
+    /// Render a resolution, or a whole registry, as Graphviz DOT or Mermaid.
+    Graph(GraphArgs),
+    /// Export pubgrub's derivation tree for a fixture with no solution.
+    Derivation(DerivationArgs),
+    /// Resolve a fixture many times with shuffled hash map orders, and say
+    /// whether the outcome ever changes.
+    Determinism(DeterminismArgs),
+    /// Resolve every fixture in a directory with provider calls failing on a
+    /// seeded schedule, and report any that panic.
+    Faults(FaultsArgs),
+    /// Resolve a fixture forcing a recorded sequence of decisions, or record
+    /// one with `--record`.
+    Replay(ReplayArgs),
+    /// Compare every fixture's outcome with the snapshot stored beside it.
+    Snapshot(SnapshotArgs),
+    /// Write a Markdown bug report for pubgrub from a fixture it fails on,
+    /// with the registry minimised.
+    IssueReport(IssueReportArgs),
+    /// Replay `gleam new`, `gleam remove` and `gleam add` commands, resolving
+    /// after each with Gleam's locking, and report the step that fails.
+    Scenario(ScenarioArgs),
+}
 
-    dbg!(&result);
+#[derive(clap::Args)]
+struct RunArgs {
+    /// Fixture to resolve. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+    /// Write every decision and provider call to this file.
+    #[arg(long)]
+    trace: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t, requires = "trace")]
+    trace_format: TraceFormatArg,
+    /// Write a self-contained HTML report of the run to this file.
+    #[arg(long, conflicts_with = "trace")]
+    report: Option<PathBuf>,
+    /// Count the provider calls pubgrub makes and print a summary.
+    #[arg(long)]
+    stats: bool,
+    /// Answer repeated dependency lookups from memory.
+    #[arg(long)]
+    cache: bool,
+}
 
-    assert!(result.is_ok());
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum TraceFormatArg {
+    /// A chronological plain text log.
+    #[default]
+    Log,
+    /// Chrome trace event JSON, for chrome://tracing or Perfetto.
+    Chrome,
 }
 
-type PackageName = String;
+#[derive(clap::Args)]
+struct CorpusArgs {
+    #[arg(default_value = "fixtures")]
+    directory: PathBuf,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
 
-type PubgrubRange = pubgrub::range::Range<Version>;
+#[derive(clap::Args)]
+struct CheckRequirementArgs {
+    /// A requirement such as ">= 1.0.0-rc2 and < 1.0.0-rc3".
+    requirement: String,
+    /// Versions to check. Defaults to versions either side of every bound.
+    versions: Vec<String>,
+    /// Match like `Version.match?(version, requirement, allow_pre: false)`.
+    #[arg(long)]
+    no_pre: bool,
+}
 
-struct Issue3201DependencyProvider {
-    available_versions: HashMap<PackageName, Vec<Version>>,
-    dependencies: HashMap<(PackageName, Version), Dependencies<PackageName, Version>>,
+#[derive(clap::Args)]
+struct ProjectArgs {
+    /// The project's gleam.toml.
+    gleam_toml: PathBuf,
+    /// Fixture whose registry to resolve against. Defaults to the bundled
+    /// issue #3201 registry.
+    #[arg(long)]
+    registry: Option<PathBuf>,
+    /// Leave out `[dev-dependencies]`.
+    #[arg(long)]
+    no_dev: bool,
+    /// Write the manifest.toml Gleam would write for the solution.
+    #[arg(long, conflicts_with = "no_dev")]
+    manifest: Option<PathBuf>,
+    /// Requirements `gleam add` put in the manifest, which it formats slightly
+    /// differently.
+    #[arg(long, requires = "manifest")]
+    added: Vec<PackageName>,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
 }
 
-impl pubgrub::solver::DependencyProvider<PackageName, Version> for Issue3201DependencyProvider {
-    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
-        &self,
-        potential_packages: impl Iterator<Item = (Name, Ver)>,
-    ) -> Result<(Name, Option<Version>), Box<dyn StdError>> {
-        Ok(choose_package_with_fewest_versions(
-            |name: &String| {
-                let Some(available_versions) = self.available_versions.get(name) else {
-                    return Vec::new().into_iter();
-                };
+#[derive(clap::Args)]
+struct DiffArgs {
+    /// A manifest.toml, or a fixture to resolve.
+    old: PathBuf,
+    /// A manifest.toml, or a fixture to resolve.
+    new: PathBuf,
+    #[command(flatten)]
+    resolution: ResolutionArgs,
+}
+
+#[derive(clap::Args)]
+struct WhyArgs {
+    package: PackageName,
+    /// A manifest.toml, or a fixture to resolve.
+    #[arg(value_name = "RESOLUTION")]
+    resolution_path: PathBuf,
+    #[command(flatten)]
+    resolution: ResolutionArgs,
+}
+
+#[derive(clap::Args)]
+struct WhyNotArgs {
+    /// Fixture to resolve. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    /// Print JSON instead of a table.
+    #[arg(long)]
+    json: bool,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct RelaxArgs {
+    /// Fixture to resolve. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    /// The most requirements to change at once.
+    #[arg(long, default_value_t = 2)]
+    max_changes: usize,
+    /// Limits for each attempt. Defaults to 10,000 steps.
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct SolutionsArgs {
+    /// Fixture to resolve. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    /// Stop after this many solutions.
+    #[arg(long, default_value_t = 100)]
+    cap: u64,
+    /// Only count the solutions.
+    #[arg(long)]
+    count: bool,
+}
+
+#[derive(clap::Args)]
+struct OptimalArgs {
+    /// Fixture to resolve. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    /// Stop after comparing this many solutions.
+    #[arg(long, default_value_t = 100_000)]
+    cap: u64,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct CnfArgs {
+    /// Fixture to encode. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    /// Write the formula here instead of printing it.
+    #[arg(long, short, conflicts_with = "model")]
+    output: Option<PathBuf>,
+    /// Decode this solver output for the fixture's formula instead.
+    #[arg(long)]
+    model: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct GraphArgs {
+    /// A manifest.toml, or a fixture to resolve.
+    path: PathBuf,
+    #[arg(long, value_enum, default_value_t)]
+    format: GraphFormat,
+    /// Every release in the fixture's registry rather than only the resolved
+    /// ones, with the resolved ones in bold if it resolves.
+    #[arg(long)]
+    all: bool,
+    #[command(flatten)]
+    resolution: ResolutionArgs,
+}
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum GraphFormat {
+    #[default]
+    Dot,
+    Mermaid,
+}
+
+#[derive(clap::Args)]
+struct DerivationArgs {
+    /// Fixture to resolve. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t)]
+    format: DerivationFormat,
+    /// Merge "no versions" incompatibilities into their neighbours first, as
+    /// the default reporter does.
+    #[arg(long)]
+    collapse: bool,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct DeterminismArgs {
+    /// Fixture to resolve. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    #[arg(long, default_value_t = 100)]
+    runs: usize,
+    /// Seed of the first run. Each later run uses the next seed.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct FaultsArgs {
+    #[arg(default_value = "fixtures")]
+    directory: PathBuf,
+    /// Runs per fixture.
+    #[arg(long, default_value_t = 20)]
+    runs: usize,
+    /// Seed of the first run. Each later run uses the next seed.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Chance that a dependency lookup fails.
+    #[arg(long, default_value_t = Faults::default().error)]
+    error_rate: f64,
+    /// Chance that a dependency lookup says the dependencies are unknown.
+    #[arg(long, default_value_t = Faults::default().unknown)]
+    unknown_rate: f64,
+    /// Chance that no version is offered for the package pubgrub picks.
+    #[arg(long, default_value_t = Faults::default().no_version)]
+    no_version_rate: f64,
+    /// Chance that a dependency lookup is slow.
+    #[arg(long, default_value_t = Faults::default().delay)]
+    delay_rate: f64,
+    /// How slow a slow lookup is, in milliseconds.
+    #[arg(long, default_value_t = 10)]
+    delay_ms: u64,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct ReplayArgs {
+    /// The decisions, one `package version` per line.
+    decisions: PathBuf,
+    /// Fixture to resolve. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    /// Resolve as usual and write the decisions made instead.
+    #[arg(long)]
+    record: bool,
+    /// The provider to record the decisions of.
+    #[arg(long, value_enum, default_value_t, requires = "record")]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct SnapshotArgs {
+    #[arg(default_value = "fixtures")]
+    directory: PathBuf,
+    /// Store the outcomes as the new snapshots instead.
+    #[arg(long)]
+    bless: bool,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct IssueReportArgs {
+    /// Fixture pubgrub fails on. Defaults to the bundled issue #3201 registry.
+    fixture: Option<PathBuf>,
+    /// Write the report here instead of printing it.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+    /// Report the fixture as it is.
+    #[arg(long)]
+    no_minimise: bool,
+}
+
+#[derive(clap::Args)]
+struct ScenarioArgs {
+    /// The commands, one per line, like `gleam add --dev startest`.
+    script: PathBuf,
+    /// Fixture whose registry the commands fetch packages from.
+    #[arg(long)]
+    registry: PathBuf,
+    /// Write the fixture the failing step resolved.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum DerivationFormat {
+    #[default]
+    Dot,
+    Json,
+}
+
+/// How to turn a manifest or fixture into a resolution.
+#[derive(clap::Args)]
+struct ResolutionArgs {
+    /// Fixture to look up the requirement ranges of manifest packages in.
+    #[arg(long)]
+    registry: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t)]
+    provider: ProviderKind,
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(clap::Args)]
+struct BudgetArgs {
+    /// Give up after this many seconds.
+    #[arg(long, value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+    /// Give up after this many decisions and dependency lookups.
+    #[arg(long)]
+    max_steps: Option<u64>,
+}
+
+impl BudgetArgs {
+    fn budget(&self) -> Budget {
+        Budget {
+            timeout: self.timeout,
+            max_steps: self.max_steps,
+        }
+    }
+}
+
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value.parse().map_err(|error| format!("{error}"))?;
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| "expected a finite, non-negative number of seconds".into())
+}
+
+#[derive(clap::Args)]
+struct BenchArgs {
+    /// Fixtures to resolve. Defaults to the bundled issue #3201 registry.
+    fixtures: Vec<PathBuf>,
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+}
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum ProviderKind {
+    /// `Issue3201DependencyProvider`, keyed on owned strings.
+    #[default]
+    HashMap,
+    /// `InternedDependencyProvider`, which does not allocate on lookup.
+    Interned,
+    /// Not pubgrub at all: the embedded CDCL SAT solver.
+    Sat,
+}
+
+impl ProviderKind {
+    fn resolve(self, fixture: &Fixture, budget: Budget) -> Outcome {
+        match self {
+            ProviderKind::HashMap => resolve_with(
+                Issue3201DependencyProvider::from_registry(&fixture.registry),
+                fixture,
+                budget,
+            ),
+            ProviderKind::Interned => resolve_with(
+                InternedDependencyProvider::from_registry(&fixture.registry),
+                fixture,
+                budget,
+            ),
+            ProviderKind::Sat => sat::outcome(
+                &fixture.registry,
+                &fixture.root,
+                &fixture.root_version,
+                budget,
+            ),
+        }
+    }
+
+    /// Resolves through a [`StatsDependencyProvider`], which needs pubgrub.
+    fn resolve_counted(
+        self,
+        fixture: &Fixture,
+        budget: Budget,
+        cache: bool,
+    ) -> Result<(Outcome, Stats), Box<dyn StdError>> {
+        match self {
+            ProviderKind::HashMap => Ok(resolve_counted(
+                Issue3201DependencyProvider::from_registry(&fixture.registry),
+                fixture,
+                budget,
+                cache,
+            )),
+            ProviderKind::Interned => Ok(resolve_counted(
+                InternedDependencyProvider::from_registry(&fixture.registry),
+                fixture,
+                budget,
+                cache,
+            )),
+            ProviderKind::Sat => Err("the SAT backend has no provider calls to count".into()),
+        }
+    }
+}
 
-                available_versions.clone().into_iter()
+fn resolve_counted(
+    provider: impl DependencyProvider<PackageName, Version>,
+    fixture: &Fixture,
+    budget: Budget,
+    cache: bool,
+) -> (Outcome, Stats) {
+    // Counting innermost, so that cache hits still take a step and show in the
+    // trace.
+    let provider = BudgetedDependencyProvider::new(
+        TracingDependencyProvider::new(StatsDependencyProvider::new(provider, cache)),
+        budget,
+    );
+    let outcome = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+    (outcome, provider.into_inner().into_inner().stats())
+}
+
+fn resolve_with(
+    provider: impl DependencyProvider<PackageName, Version>,
+    fixture: &Fixture,
+    budget: Budget,
+) -> Outcome {
+    let provider =
+        BudgetedDependencyProvider::new(TracingDependencyProvider::new(provider), budget);
+    outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command.unwrap_or(Command::Run(cli.run)) {
+        Command::Run(args) => run(args),
+        Command::Bench(args) => bench(args),
+        Command::Corpus(args) => corpus(args),
+        Command::CheckRequirement(args) => check_requirement(args),
+        Command::Project(args) => project(args),
+        Command::Diff(args) => diff(args),
+        Command::Why(args) => why(args),
+        Command::WhyNot(args) => why_not(args),
+        Command::Relax(args) => relax(args),
+        Command::Solutions(args) => solutions(args),
+        Command::Optimal(args) => optimal(args),
+        Command::Cnf(args) => cnf(args),
+        Command::Graph(args) => graph(args),
+        Command::Derivation(args) => derivation(args),
+        Command::Determinism(args) => determinism(args),
+        Command::Faults(args) => faults(args),
+        Command::Replay(args) => replay(args),
+        Command::Snapshot(args) => snapshot(args),
+        Command::IssueReport(args) => issue_report(args),
+        Command::Scenario(args) => scenario(args),
+    };
+    result.unwrap_or_else(|error| {
+        eprintln!("error: {error}");
+        ExitCode::FAILURE
+    })
+}
+
+fn load_fixture(path: Option<&PathBuf>) -> Result<Fixture, Box<dyn StdError>> {
+    match path {
+        Some(path) => Ok(Fixture::load(path)?),
+        None => Ok(Fixture::issue_3201()),
+    }
+}
+
+fn run(args: RunArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let _trace = match &args.trace {
+        Some(path) => Some(trace::install(
+            path,
+            match args.trace_format {
+                TraceFormatArg::Log => TraceFormat::Log,
+                TraceFormatArg::Chrome => TraceFormat::Chrome,
             },
-            potential_packages.into_iter(),
-        ))
+        )?),
+        None => None,
+    };
+    let budget = args.budget.budget();
+    let resolve = || match args.stats || args.cache {
+        true => args
+            .provider
+            .resolve_counted(&fixture, budget, args.cache)
+            .map(|(outcome, stats)| (outcome, Some(stats))),
+        false => Ok((args.provider.resolve(&fixture, budget), None)),
+    };
+    let (outcome, stats) = match &args.report {
+        Some(path) => {
+            let (resolved, events) = trace::record(resolve);
+            let (outcome, stats) = resolved?;
+            let provider = args
+                .provider
+                .to_possible_value()
+                .expect("no skipped variants");
+            std::fs::write(
+                path,
+                report::html(&fixture, provider.get_name(), &outcome, &events),
+            )?;
+            (outcome, stats)
+        }
+        None => resolve()?,
+    };
+    print!("{outcome}");
+    if let Some(stats) = stats.filter(|_| args.stats) {
+        print!("\n{stats}");
     }
+    Ok(if outcome.is_solved() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn project(args: ProjectArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let project = Project::load(&args.gleam_toml)?;
+    let registry = load_fixture(args.registry.as_ref())?.registry;
+    let fixture = project.fixture(&registry, !args.no_dev);
 
-    fn get_dependencies(
-        &self,
-        name: &PackageName,
-        version: &Version,
-    ) -> Result<Dependencies<PackageName, Version>, Box<dyn StdError>> {
-        self.dependencies
-            .get(&(name.clone(), version.clone()))
-            .cloned()
-            .ok_or_else(|| "failed to get dependencies".into())
+    let outcome = args.provider.resolve(&fixture, args.budget.budget());
+    print!("{outcome}");
+    let Outcome::Solved(solution) = &outcome else {
+        return Ok(ExitCode::FAILURE);
+    };
+    if !args.no_dev {
+        let dev_only = project.dev_only(&fixture.registry, solution);
+        if !dev_only.is_empty() {
+            println!("only through dev-dependencies:");
+            for name in dev_only {
+                println!("  {name} {}", solution[&name]);
+            }
+        }
     }
+    if let Some(path) = &args.manifest {
+        let mut manifest = Manifest::from_solution(&project, &fixture.registry, solution)?;
+        manifest.added.extend(args.added);
+        std::fs::write(path, manifest.to_toml())?;
+        println!("wrote {}", path.display());
+    }
+    Ok(ExitCode::SUCCESS)
 }
 
-impl Issue3201DependencyProvider {
-    pub fn new() -> Self {
-        let mut this = Self {
-            available_versions: HashMap::default(),
-            dependencies: HashMap::default(),
-        };
+fn diff(args: DiffArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let (old, _) = load_resolution(&args.old, &args.resolution)?;
+    let (new, _) = load_resolution(&args.new, &args.resolution)?;
+    let changes = diff::diff(&old, &new);
+    if changes.is_empty() {
+        println!("no changes");
+    }
+    for change in &changes {
+        print!("{change}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
-        this.available_versions
-            .entry("gleam_add_issue_2024_05_26".to_string())
-            .or_default()
-            .push(Version::parse("0.0.0").unwrap());
-        this.available_versions
-            .entry("argv".to_string())
-            .or_default()
-            .push(Version::parse("1.0.2").unwrap());
-        this.available_versions
-            .entry("birl".to_string())
-            .or_default()
-            .push(Version::parse("1.7.0").unwrap());
-        this.available_versions
-            .entry("gleam_javascript".to_string())
-            .or_default()
-            .push(Version::parse("0.8.0").unwrap());
-        this.available_versions
-            .entry("gleam_community_colour".to_string())
-            .or_default()
-            .push(Version::parse("1.4.0").unwrap());
-        this.available_versions
-            .entry("gleam_community_ansi".to_string())
-            .or_default()
-            .push(Version::parse("1.4.0").unwrap());
-        this.available_versions
-            .entry("gleam_erlang".to_string())
-            .or_default()
-            .push(Version::parse("0.25.0").unwrap());
-        this.available_versions
-            .entry("tom".to_string())
-            .or_default()
-            .push(Version::parse("0.3.0").unwrap());
-        this.available_versions
-            .entry("thoas".to_string())
-            .or_default()
-            .push(Version::parse("1.2.1").unwrap());
-        this.available_versions
-            .entry("glint".to_string())
-            .or_default()
-            .push(Version::parse("1.0.0-rc2").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.14.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.13.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.12.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.11.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.10.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.9.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.8.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.7.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.6.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.5.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.4.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.3.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.2.0").unwrap());
-        this.available_versions
-            .entry("wisp".to_string())
-            .or_default()
-            .push(Version::parse("0.1.0").unwrap());
-        this.available_versions
-            .entry("snag".to_string())
-            .or_default()
-            .push(Version::parse("0.3.0").unwrap());
-        this.available_versions
-            .entry("gleam_otp".to_string())
-            .or_default()
-            .push(Version::parse("0.10.0").unwrap());
-        this.available_versions
-            .entry("exception".to_string())
-            .or_default()
-            .push(Version::parse("2.0.0").unwrap());
-        this.available_versions
-            .entry("ranger".to_string())
-            .or_default()
-            .push(Version::parse("1.2.0").unwrap());
-        this.available_versions
-            .entry("simplifile".to_string())
-            .or_default()
-            .push(Version::parse("1.7.0").unwrap());
-        this.available_versions
-            .entry("filepath".to_string())
-            .or_default()
-            .push(Version::parse("1.0.0").unwrap());
-        this.available_versions
-            .entry("startest".to_string())
-            .or_default()
-            .push(Version::parse("0.2.4").unwrap());
-        this.available_versions
-            .entry("gleam_json".to_string())
-            .or_default()
-            .push(Version::parse("1.0.1").unwrap());
-        this.available_versions
-            .entry("bigben".to_string())
-            .or_default()
-            .push(Version::parse("1.0.0").unwrap());
-        this.available_versions
-            .entry("gleam_stdlib".to_string())
-            .or_default()
-            .push(Version::parse("0.38.0").unwrap());
-
-        let _ = this.dependencies.insert(
-            (
-                "gleam_add_issue_2024_05_26".to_string(),
-                Version::parse("0.0.0").unwrap(),
-            ),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "bigben".to_string(),
-                    Range::new("1.0.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new("0.38.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "gleam_javascript".to_string(),
-                    Range::new("0.8.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "gleam_community_colour".to_string(),
-                    Range::new("1.4.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "gleam_community_ansi".to_string(),
-                    Range::new("1.4.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "gleam_erlang".to_string(),
-                    Range::new("0.25.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "tom".to_string(),
-                    Range::new("0.3.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "thoas".to_string(),
-                    Range::new("1.2.1".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "glint".to_string(),
-                    Range::new("1.0.0-rc2".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "wisp".to_string(),
-                    pubgrub::range::Range::any(),
-                    // Range::new("*".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "snag".to_string(),
-                    Range::new("0.3.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "gleam_otp".to_string(),
-                    Range::new("0.10.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "simplifile".to_string(),
-                    Range::new("1.7.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "ranger".to_string(),
-                    Range::new("1.2.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "exception".to_string(),
-                    Range::new("2.0.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "filepath".to_string(),
-                    Range::new("1.0.0".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "gleam_json".to_string(),
-                    Range::new("1.0.1".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "startest".to_string(),
-                    Range::new("0.2.4".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "argv".to_string(),
-                    Range::new("1.0.2".to_string()).to_pubgrub().unwrap(),
-                ),
-                (
-                    "birl".to_string(),
-                    Range::new("1.7.0".to_string()).to_pubgrub().unwrap(),
-                ),
-            ])),
+fn why(args: WhyArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let (resolution, registry) = load_resolution(&args.resolution_path, &args.resolution)?;
+    let paths = why::paths(&resolution, &args.package);
+    if paths.is_empty() {
+        println!("{} is not required by anything", args.package);
+        return Ok(ExitCode::FAILURE);
+    }
+    for path in &paths {
+        println!("{path}");
+    }
+
+    let available: Vec<_> = registry
+        .as_ref()
+        .and_then(|registry| registry.releases(&args.package))
+        .map(|releases| releases.keys().cloned().collect())
+        .unwrap_or_default();
+    if let Some(edge) = why::tightest(&resolution, &args.package, &available) {
+        println!("tightest: {edge}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn why_not(args: WhyNotArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let Outcome::Solved(solution) = args.provider.resolve(&fixture, args.budget.budget()) else {
+        return Err("the fixture does not resolve".into());
+    };
+
+    // Checking transitive conflicts resolves again, which may panic.
+    let held_back = outcome::quietly(|| {
+        why_not::held_back(&fixture.root, &fixture.registry, &solution, |fixture| {
+            args.provider.resolve(fixture, args.budget.budget())
+        })
+    });
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&held_back)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+    if held_back.is_empty() {
+        println!("every package is on its newest release");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let width = |column: &str, cell: &dyn Fn(&why_not::HeldBack) -> String| {
+        held_back
+            .iter()
+            .map(|row| cell(row).len())
+            .chain([column.len()])
+            .max()
+            .unwrap_or(0)
+    };
+    let package = width("package", &|row| row.package.clone());
+    let chosen = width("chosen", &|row| row.chosen.to_string());
+    let newest = width("newest", &|row| row.newest.to_string());
+    println!(
+        "{:<package$}  {:<chosen$}  {:<newest$}  held back by",
+        "package", "chosen", "newest"
+    );
+    for row in &held_back {
+        let mut reasons = row.reasons.iter().map(ToString::to_string);
+        println!(
+            "{:<package$}  {:<chosen$}  {:<newest$}  {}",
+            row.package,
+            row.chosen.to_string(),
+            row.newest.to_string(),
+            reasons
+                .next()
+                .unwrap_or_else(|| "nothing, pubgrub chose an older version".into()),
         );
-        let _ = this.dependencies.insert(
-            (
-                "gleam_stdlib".to_string(),
-                Version::parse("0.38.0").unwrap(),
-            ),
-            Dependencies::Known(Map::from_iter([])),
+        for reason in reasons {
+            println!(
+                "{:width$}  {reason}",
+                "",
+                width = package + chosen + newest + 4
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn relax(args: RelaxArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let budget = args.budget.budget();
+    let budget = if budget.is_unlimited() {
+        Budget {
+            max_steps: Some(10_000),
+            ..budget
+        }
+    } else {
+        budget
+    };
+
+    // pubgrub is still asked about each answer, and may panic.
+    let suggestions = match outcome::quietly(|| relax::suggest(&fixture, args.max_changes, budget))
+    {
+        Ok(suggestions) => suggestions,
+        Err(relax::RelaxError::Solvable) => {
+            println!("the fixture already resolves");
+            return Ok(ExitCode::SUCCESS);
+        }
+        Err(error) => return Err(error.into()),
+    };
+    if suggestions.is_empty() {
+        println!(
+            "no combination of up to {} relaxations resolves",
+            args.max_changes
         );
-        let _ = this.dependencies.insert(
-            ("argv".to_string(), Version::parse("1.0.2").unwrap()),
-            Dependencies::Known(Map::from_iter([])),
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let print = |suggestions: &[&relax::Suggestion]| {
+        for (number, suggestion) in suggestions.iter().enumerate() {
+            for (index, relaxation) in suggestion.relaxations.iter().enumerate() {
+                if index == 0 {
+                    println!("{}. {relaxation}", number + 1);
+                } else {
+                    println!("   and {relaxation}");
+                }
+            }
+            if let Some(message) = &suggestion.panicked {
+                println!("   pubgrub panics: {message}");
+            }
+        }
+    };
+    let (panicking, resolving): (Vec<_>, Vec<_>) = suggestions
+        .iter()
+        .partition(|suggestion| suggestion.panicked.is_some());
+    print(&resolving);
+    if !panicking.is_empty() {
+        if !resolving.is_empty() {
+            println!();
+        }
+        println!("these also make it solvable, but pubgrub panics instead of resolving:");
+        print(&panicking);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn solutions(args: SolutionsArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let at_least = |complete| if complete { "" } else { "at least " };
+
+    if args.count {
+        let count = enumerate::count(
+            &fixture.registry,
+            &fixture.root,
+            &fixture.root_version,
+            args.cap,
         );
-        let _ = this.dependencies.insert(
-            ("birl".to_string(), Version::parse("1.7.0").unwrap()),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new(">= 0.37.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "ranger".to_string(),
-                    Range::new(">= 1.2.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-            ])),
+        println!("{}{} solutions", at_least(count.complete), count.count);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let solutions = enumerate::solutions(
+        &fixture.registry,
+        &fixture.root,
+        &fixture.root_version,
+        args.cap.try_into()?,
+    );
+    for solution in &solutions.found {
+        let line: Vec<_> = solution
+            .iter()
+            .filter(|(name, _)| **name != fixture.root)
+            .map(|(name, version)| format!("{name} {version}"))
+            .collect();
+        println!("{}", line.join(", "));
+    }
+    println!(
+        "{}{} solutions",
+        at_least(solutions.complete),
+        solutions.found.len()
+    );
+
+    let outcome = outcome::quietly(|| ProviderKind::Interned.resolve(&fixture, Budget::default()));
+    match &outcome {
+        Outcome::Solved(solution) if solutions.found.contains(solution) => {
+            println!("pubgrub's solution is among them");
+        }
+        Outcome::Solved(_) if solutions.complete => {
+            println!("pubgrub's solution is not among them");
+            return Ok(ExitCode::FAILURE);
+        }
+        Outcome::Solved(_) => println!("pubgrub's solution is not among those listed"),
+        Outcome::NoSolution(_) | Outcome::Unsatisfiable if solutions.found.is_empty() => {
+            println!("pubgrub also found no solution");
+        }
+        outcome => {
+            println!("pubgrub: {}", outcome.to_string().trim_end());
+            if !solutions.found.is_empty() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn optimal(args: OptimalArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let newest = optimal::newest(
+        &fixture.registry,
+        &fixture.root,
+        &fixture.root_version,
+        args.cap,
+    );
+    let Some(best) = &newest.solution else {
+        println!("no solution");
+        return Ok(ExitCode::SUCCESS);
+    };
+    if !newest.complete {
+        println!(
+            "stopped after {} solutions, so a newer one may exist",
+            newest.compared
         );
-        let _ = this.dependencies.insert(
-            ("bigben".to_string(), Version::parse("1.0.0").unwrap()),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "gleam_otp".to_string(),
-                    Range::new(">= 0.10.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new(">= 0.34.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_erlang".to_string(),
-                    Range::new(">= 0.25.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "birl".to_string(),
-                    Range::new(">= 1.6.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-            ])),
+    }
+
+    let outcome = outcome::quietly(|| args.provider.resolve(&fixture, args.budget.budget()));
+    let Outcome::Solved(chosen) = &outcome else {
+        println!("pubgrub: {}", outcome.to_string().trim_end());
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let priority = optimal::priority(&fixture.registry, &fixture.root, &fixture.root_version);
+    if optimal::compare(&priority, chosen, best).is_ge() {
+        if newest.complete {
+            println!("pubgrub found the newest solution");
+        } else {
+            println!(
+                "pubgrub's solution is at least as new as the best of the {} compared",
+                newest.compared
+            );
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+    for stale in optimal::stale(
+        &fixture.registry,
+        &fixture.root,
+        &fixture.root_version,
+        chosen,
+        best,
+    ) {
+        println!("{stale}");
+    }
+    Ok(ExitCode::FAILURE)
+}
+
+fn cnf(args: CnfArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let cnf = cnf::encode(&fixture.registry, &fixture.root, &fixture.root_version);
+    let Some(model) = &args.model else {
+        match &args.output {
+            Some(path) => std::fs::write(path, cnf.to_dimacs())?,
+            None => print!("{}", cnf.to_dimacs()),
+        }
+        return Ok(ExitCode::SUCCESS);
+    };
+
+    match cnf.decode(&fixture.registry, &std::fs::read_to_string(model)?)? {
+        Some(solution) => {
+            for (name, version) in solution.iter().filter(|(name, _)| **name != fixture.root) {
+                println!("{name} {version}");
+            }
+        }
+        None => println!("no solution"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn graph(args: GraphArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let graph = if args.all {
+        let fixture = Fixture::load(&args.path)?;
+        let outcome = outcome::quietly(|| {
+            args.resolution
+                .provider
+                .resolve(&fixture, args.resolution.budget.budget())
+        });
+        let solution = match &outcome {
+            Outcome::Solved(solution) => Some(solution),
+            _ => None,
+        };
+        graph::registry(&fixture.registry, solution)
+    } else {
+        graph::resolution(&load_resolution(&args.path, &args.resolution)?.0)
+    };
+    match args.format {
+        GraphFormat::Dot => print!("{}", graph.to_dot()),
+        GraphFormat::Mermaid => print!("{}", graph.to_mermaid()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn derivation(args: DerivationArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let outcome = outcome::quietly(|| args.provider.resolve(&fixture, args.budget.budget()));
+    let Outcome::NoSolution(mut tree) = outcome else {
+        eprintln!(
+            "no derivation tree to export: {}",
+            outcome.to_string().trim_end()
         );
-        let _ = this.dependencies.insert(
-            (
-                "gleam_javascript".to_string(),
-                Version::parse("0.8.0").unwrap(),
-            ),
-            Dependencies::Known(Map::from_iter([(
-                "gleam_stdlib".to_string(),
-                Range::new(">= 0.19.0 and < 2.0.0".to_string())
-                    .to_pubgrub()
-                    .unwrap(),
-            )])),
+        return Ok(ExitCode::FAILURE);
+    };
+    if args.collapse {
+        tree.collapse_no_versions();
+    }
+
+    let graph = DerivationGraph::new(&tree);
+    match args.format {
+        DerivationFormat::Dot => print!("{}", graph.to_dot()),
+        DerivationFormat::Json => println!("{}", serde_json::to_string_pretty(&graph)?),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn determinism(args: DeterminismArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let report = outcome::quietly(|| {
+        determinism::check(&fixture, args.runs, args.seed, args.budget.budget())
+    });
+
+    for variant in &report.variants {
+        let seeds: Vec<_> = variant.seeds.iter().take(10).map(u64::to_string).collect();
+        let more = if variant.seeds.len() > seeds.len() {
+            ", ..."
+        } else {
+            ""
+        };
+        println!(
+            "{} of {} runs (seeds {}{more}):",
+            variant.seeds.len(),
+            report.runs,
+            seeds.join(", ")
         );
-        let _ = this.dependencies.insert(
-            (
-                "gleam_community_colour".to_string(),
-                Version::parse("1.4.0").unwrap(),
+        for line in variant.outcome.lines() {
+            println!("  {line}");
+        }
+    }
+    Ok(if report.is_deterministic() {
+        println!("deterministic");
+        ExitCode::SUCCESS
+    } else {
+        println!("{} different outcomes", report.variants.len());
+        ExitCode::FAILURE
+    })
+}
+
+fn faults(args: FaultsArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let faults = Faults {
+        seed: args.seed,
+        error: args.error_rate,
+        unknown: args.unknown_rate,
+        delay: args.delay_rate,
+        delay_for: Duration::from_millis(args.delay_ms),
+        no_version: args.no_version_rate,
+    };
+    let budget = args.budget.budget();
+    // Like a corpus run, this must always finish.
+    let budget = if budget.is_unlimited() {
+        Budget {
+            timeout: Some(Duration::from_secs(60)),
+            ..budget
+        }
+    } else {
+        budget
+    };
+
+    outcome::quietly(|| {
+        let mut robust = true;
+        for path in fixture::corpus(&args.directory)? {
+            let fixture = match Fixture::load(&path) {
+                Ok(fixture) => fixture,
+                Err(error) => {
+                    println!("{}: invalid fixture: {error}", path.display());
+                    continue;
+                }
+            };
+            let robustness = faults::check(&fixture, faults, args.runs, budget);
+            let mut kinds = BTreeMap::new();
+            for run in &robustness.runs {
+                *kinds.entry(run.outcome.kind()).or_insert(0) += 1;
+            }
+            let kinds: Vec<_> = kinds
+                .iter()
+                .map(|(kind, count)| format!("{count} {kind}"))
+                .collect();
+            println!(
+                "{}: {} (without faults: {})",
+                path.display(),
+                kinds.join(", "),
+                summarise(&robustness.baseline)
+            );
+            for run in robustness.new_panics() {
+                robust = false;
+                let injected = run.injected;
+                println!(
+                    "  seed {} {}, after {} errors, {} unknown, {} delays, {} missing versions",
+                    run.seed,
+                    summarise(&run.outcome),
+                    injected.errors,
+                    injected.unknown,
+                    injected.delays,
+                    injected.no_versions
+                );
+            }
+        }
+
+        Ok(if robust {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        })
+    })
+}
+
+fn replay(args: ReplayArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let budget = args.budget.budget();
+    let outcome = if args.record {
+        let (outcome, decisions) = match args.provider {
+            ProviderKind::HashMap => record_decisions(
+                Issue3201DependencyProvider::from_registry(&fixture.registry),
+                &fixture,
+                budget,
             ),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new(">= 0.34.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_json".to_string(),
-                    Range::new(">= 0.7.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-            ])),
-        );
-        let _ = this.dependencies.insert(
-            (
-                "gleam_community_ansi".to_string(),
-                Version::parse("1.4.0").unwrap(),
+            ProviderKind::Interned => record_decisions(
+                InternedDependencyProvider::from_registry(&fixture.registry),
+                &fixture,
+                budget,
             ),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "gleam_community_colour".to_string(),
-                    Range::new(">= 1.3.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new(">= 0.34.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-            ])),
+            ProviderKind::Sat => return Err("the SAT backend makes no pubgrub decisions".into()),
+        };
+        std::fs::write(&args.decisions, replay::write(&decisions))?;
+        println!(
+            "recorded {} decisions to {}",
+            decisions.len(),
+            args.decisions.display()
         );
-        let _ = this.dependencies.insert(
-            (
-                "gleam_erlang".to_string(),
-                Version::parse("0.25.0").unwrap(),
+        outcome
+    } else {
+        let decisions = replay::parse(&std::fs::read_to_string(&args.decisions)?)?;
+        let provider = BudgetedDependencyProvider::new(
+            ReplayDependencyProvider::new(
+                TracingDependencyProvider::new(InternedDependencyProvider::from_registry(
+                    &fixture.registry,
+                )),
+                decisions.clone(),
             ),
-            Dependencies::Known(Map::from_iter([(
-                "gleam_stdlib".to_string(),
-                Range::new(">= 0.33.0 and < 2.0.0".to_string())
-                    .to_pubgrub()
-                    .unwrap(),
-            )])),
-        );
-        let _ = this.dependencies.insert(
-            ("tom".to_string(), Version::parse("0.3.0").unwrap()),
-            Dependencies::Known(Map::from_iter([(
-                "gleam_stdlib".to_string(),
-                Range::new(">= 0.33.0 and < 1.0.0".to_string())
-                    .to_pubgrub()
-                    .unwrap(),
-            )])),
-        );
-        let _ = this.dependencies.insert(
-            ("thoas".to_string(), Version::parse("1.2.1").unwrap()),
-            Dependencies::Known(Map::from_iter([])),
+            budget,
         );
-        let _ = this.dependencies.insert(
-            ("glint".to_string(), Version::parse("1.0.0-rc2").unwrap()),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "gleam_community_colour".to_string(),
-                    Range::new(">= 1.0.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_community_ansi".to_string(),
-                    Range::new(">= 1.0.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "snag".to_string(),
-                    Range::new(">= 0.3.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new(">= 0.36.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-            ])),
+        let outcome = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+        println!(
+            "replayed {} of {} decisions",
+            provider.into_inner().replayed(),
+            decisions.len()
         );
-        let _ = this.dependencies.insert(
-            ("snag".to_string(), Version::parse("0.3.0").unwrap()),
-            Dependencies::Known(Map::from_iter([(
-                "gleam_stdlib".to_string(),
-                Range::new(">= 0.34.0 and < 1.0.0".to_string())
-                    .to_pubgrub()
-                    .unwrap(),
-            )])),
-        );
-        let _ = this.dependencies.insert(
-            ("gleam_otp".to_string(), Version::parse("0.10.0").unwrap()),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "gleam_erlang".to_string(),
-                    Range::new(">= 0.22.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new(">= 0.32.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-            ])),
-        );
-        let _ = this.dependencies.insert(
-            ("exception".to_string(), Version::parse("2.0.0").unwrap()),
-            Dependencies::Known(Map::from_iter([(
-                "gleam_stdlib".to_string(),
-                Range::new(">= 0.30.0 and < 2.0.0".to_string())
-                    .to_pubgrub()
-                    .unwrap(),
-            )])),
-        );
-        let _ = this.dependencies.insert(
-            ("ranger".to_string(), Version::parse("1.2.0").unwrap()),
-            Dependencies::Known(Map::from_iter([(
-                "gleam_stdlib".to_string(),
-                Range::new(">= 0.36.0 and < 2.0.0".to_string())
-                    .to_pubgrub()
-                    .unwrap(),
-            )])),
-        );
-        let _ = this.dependencies.insert(
-            ("simplifile".to_string(), Version::parse("1.7.0").unwrap()),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "filepath".to_string(),
-                    Range::new(">= 1.0.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new(">= 0.34.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-            ])),
-        );
-        let _ = this.dependencies.insert(
-            ("filepath".to_string(), Version::parse("1.0.0").unwrap()),
-            Dependencies::Known(Map::from_iter([(
-                "gleam_stdlib".to_string(),
-                Range::new(">= 0.32.0 and < 1.0.0".to_string())
-                    .to_pubgrub()
-                    .unwrap(),
-            )])),
-        );
-        let _ = this.dependencies.insert(
-            ("startest".to_string(), Version::parse("0.2.4").unwrap()),
-            Dependencies::Known(Map::from_iter([
-                (
-                    "argv".to_string(),
-                    Range::new(">= 1.0.2 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_stdlib".to_string(),
-                    Range::new(">= 0.36.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "exception".to_string(),
-                    Range::new(">= 2.0.0 and < 3.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "simplifile".to_string(),
-                    Range::new(">= 1.7.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_javascript".to_string(),
-                    Range::new(">= 0.8.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_community_ansi".to_string(),
-                    Range::new(">= 1.4.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "gleam_erlang".to_string(),
-                    Range::new(">= 0.25.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "tom".to_string(),
-                    Range::new(">= 0.3.0 and < 1.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "glint".to_string(),
-                    Range::new(">= 1.0.0-rc2 and < 1.0.0-rc3".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "bigben".to_string(),
-                    Range::new(">= 1.0.0 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-                (
-                    "birl".to_string(),
-                    Range::new(">= 1.6.1 and < 2.0.0".to_string())
-                        .to_pubgrub()
-                        .unwrap(),
-                ),
-            ])),
+        outcome
+    };
+    print!("{outcome}");
+    Ok(if outcome.is_solved() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn record_decisions(
+    provider: impl DependencyProvider<PackageName, Version>,
+    fixture: &Fixture,
+    budget: Budget,
+) -> (Outcome, Vec<replay::Decision>) {
+    let provider = RecordingDependencyProvider::new(BudgetedDependencyProvider::new(
+        TracingDependencyProvider::new(provider),
+        budget,
+    ));
+    let outcome = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+    (outcome, provider.decisions())
+}
+
+/// Reads a manifest, or resolves a fixture. The registry is the fixture's own
+/// or the one passed with `--registry`.
+fn load_resolution(
+    path: &PathBuf,
+    args: &ResolutionArgs,
+) -> Result<(Resolution, Option<Registry>), Box<dyn StdError>> {
+    let source = std::fs::read_to_string(path)?;
+    // Fixtures always have a `[root]` table and manifests never do.
+    if !source.parse::<toml::Table>()?.contains_key("root") {
+        let manifest = Manifest::from_toml(&source)?;
+        let registry = match &args.registry {
+            Some(path) => Some(Fixture::load(path)?.registry),
+            None => None,
+        };
+        return Ok((
+            Resolution::from_manifest(&manifest, registry.as_ref()),
+            registry,
+        ));
+    }
+    let fixture = Fixture::from_toml(&source)?;
+    match args.provider.resolve(&fixture, args.budget.budget()) {
+        Outcome::Solved(solution) => Ok((
+            Resolution::from_solution(&fixture.root, &fixture.registry, &solution),
+            Some(fixture.registry),
+        )),
+        outcome => Err(format!("{} did not resolve: {outcome}", path.display()).into()),
+    }
+}
+
+fn corpus(args: CorpusArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let budget = args.budget.budget();
+    // A corpus run must always finish, so never run without a limit.
+    let budget = if budget.is_unlimited() {
+        Budget {
+            timeout: Some(Duration::from_secs(60)),
+            ..budget
+        }
+    } else {
+        budget
+    };
+
+    outcome::quietly(|| {
+        for path in fixture::corpus(&args.directory)? {
+            let line = match Fixture::load(&path) {
+                Ok(fixture) => summarise(&args.provider.resolve(&fixture, budget)),
+                Err(error) => format!("invalid fixture: {error}"),
+            };
+            println!("{}: {line}", path.display());
+        }
+
+        Ok(ExitCode::SUCCESS)
+    })
+}
+
+fn snapshot(args: SnapshotArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    outcome::quietly(|| {
+        let mut unchanged = true;
+        for path in fixture::corpus(&args.directory)? {
+            let fixture = Fixture::load(&path)?;
+            let rendered = snapshot::render(&args.provider.resolve(&fixture, args.budget.budget()));
+            let status = snapshot::compare(&path, &rendered)?;
+            if args.bless && status != Status::Unchanged {
+                snapshot::bless(&path, &rendered)?;
+                println!("{}: blessed", path.display());
+                continue;
+            }
+            match status {
+                Status::Unchanged => println!("{}: unchanged", path.display()),
+                Status::New => {
+                    unchanged = false;
+                    println!(
+                        "{}: no snapshot, run with --bless to record one",
+                        path.display()
+                    );
+                }
+                Status::Changed { diff } => {
+                    unchanged = false;
+                    println!("{}: changed", path.display());
+                    for line in diff.lines() {
+                        println!("  {line}");
+                    }
+                }
+            }
+        }
+
+        Ok(if unchanged {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        })
+    })
+}
+
+fn issue_report(args: IssueReportArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixture = load_fixture(args.fixture.as_ref())?;
+    let issue = issue_report::prepare(&fixture, !args.no_minimise)?;
+    match &args.output {
+        Some(path) => std::fs::write(path, issue.markdown())?,
+        None => print!("{}", issue.markdown()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn scenario(args: ScenarioArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let steps = scenario::parse(&std::fs::read_to_string(&args.script)?)?;
+    let registry = Fixture::load(&args.registry)?.registry;
+
+    let run = outcome::quietly(|| {
+        scenario::run(&steps, &registry, |fixture| {
+            args.provider.resolve(fixture, args.budget.budget())
+        })
+    });
+
+    for (number, report) in (1..).zip(&run.steps) {
+        println!("{number}. {}", report.step);
+        match (&report.result, &report.manifest) {
+            (Ok(()), None) => println!("   nothing to resolve"),
+            (Ok(()), Some(manifest)) => println!(
+                "   resolved, packages: {}, locked: {}",
+                manifest.packages.len(),
+                report.locked.len()
+            ),
+            (Err(error), _) => {
+                if !report.locked.is_empty() {
+                    let locked: Vec<_> = report
+                        .locked
+                        .iter()
+                        .map(|(name, version)| format!("{name} {version}"))
+                        .collect();
+                    println!("   locked: {}", locked.join(", "));
+                }
+                for line in error.to_string().lines() {
+                    println!("   {line}");
+                }
+            }
+        }
+    }
+    let Some((number, report)) = run.failure() else {
+        println!("every step succeeded");
+        return Ok(ExitCode::SUCCESS);
+    };
+    println!("step {number} failed: {}", report.step);
+    if let (Some(path), Some(fixture)) = (&args.output, &report.fixture) {
+        std::fs::write(path, fixture.to_toml())?;
+        println!("wrote {}", path.display());
+    }
+    Ok(ExitCode::FAILURE)
+}
+
+fn summarise(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Solved(solution) => format!("solved ({} packages)", solution.len()),
+        Outcome::NoSolution(_) | Outcome::Unsatisfiable => "no solution".into(),
+        Outcome::Failed(message) => format!("failed: {message}"),
+        Outcome::Panicked(message) => format!("panicked: {message}"),
+        Outcome::TimedOut(exceeded) => format!("timed out after {} steps", exceeded.steps),
+    }
+}
+
+fn check_requirement(args: CheckRequirementArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let versions = if args.versions.is_empty() {
+        HexRequirement::parse(&args.requirement)?.probe_versions()
+    } else {
+        args.versions
+            .iter()
+            .map(|version| Version::parse(version))
+            .collect::<Result<_, _>>()?
+    };
+
+    let comparisons = hex_semantics::compare(&args.requirement, &versions, !args.no_pre)?;
+    let width = versions
+        .iter()
+        .map(|version| version.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("version".len());
+    let yes_no = |matches| if matches { "yes" } else { "no" };
+    println!("{:<width$}  hex  pubgrub", "version");
+    for comparison in &comparisons {
+        let line = format!(
+            "{:<width$}  {:<3}  {:<7}  {}",
+            comparison.version.to_string(),
+            yes_no(comparison.hex),
+            yes_no(comparison.pubgrub),
+            if comparison.agrees() {
+                ""
+            } else {
+                "<- disagree"
+            },
         );
+        println!("{}", line.trim_end());
+    }
+
+    Ok(if comparisons.iter().all(Comparison::agrees) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn bench(args: BenchArgs) -> Result<ExitCode, Box<dyn StdError>> {
+    let fixtures = if args.fixtures.is_empty() {
+        vec![None]
+    } else {
+        args.fixtures.iter().map(Some).collect()
+    };
+
+    // Fixtures that panic would otherwise print a message every iteration.
+    outcome::quietly(|| {
+        for path in fixtures {
+            let fixture = load_fixture(path)?;
+            println!(
+                "{} ({} packages, {} releases)",
+                path.map_or("issue_3201".into(), |path| path.display().to_string()),
+                fixture.registry.package_count(),
+                fixture.registry.release_count(),
+            );
+
+            let mut kinds = Vec::new();
+            for provider in ProviderKind::value_variants() {
+                let build = Instant::now();
+                let timing = match provider {
+                    ProviderKind::HashMap => {
+                        let provider =
+                            Issue3201DependencyProvider::from_registry(&fixture.registry);
+                        time_resolution(args.iterations, || {
+                            outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+                        })
+                    }
+                    ProviderKind::Interned => {
+                        let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+                        time_resolution(args.iterations, || {
+                            outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+                        })
+                    }
+                    ProviderKind::Sat => time_resolution(args.iterations, || {
+                        sat::outcome(
+                            &fixture.registry,
+                            &fixture.root,
+                            &fixture.root_version,
+                            Budget::default(),
+                        )
+                    }),
+                };
+                let name = provider.to_possible_value().expect("no skipped variants");
+                println!(
+                    "  {:<10} {:<12} mean {:?}, fastest {:?}, total with setup {:?}",
+                    name.get_name(),
+                    timing.kind,
+                    timing.mean,
+                    timing.fastest,
+                    build.elapsed(),
+                );
+                kinds.push(timing.kind);
+            }
+            if kinds.windows(2).any(|pair| pair[0] != pair[1]) {
+                println!("  warning: providers disagree on the outcome");
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    })
+}
+
+struct Timing {
+    kind: &'static str,
+    mean: Duration,
+    fastest: Duration,
+}
 
-        this
+fn time_resolution(iterations: u32, resolve: impl Fn() -> Outcome) -> Timing {
+    let mut timing = Timing {
+        kind: "",
+        mean: Duration::ZERO,
+        fastest: Duration::MAX,
+    };
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let outcome = resolve();
+        let elapsed = start.elapsed();
+        timing.fastest = timing.fastest.min(elapsed);
+        total += elapsed;
+        timing.kind = outcome.kind();
     }
+    timing.mean = total / iterations;
+    timing
 }
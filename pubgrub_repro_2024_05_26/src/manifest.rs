@@ -0,0 +1,264 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::Path,
+};
+
+use hexpm::version::Version;
+use serde::Deserialize;
+
+use crate::{project::Project, registry::Registry, PackageName};
+
+/// A Gleam `manifest.toml`, as written by `gleam build` and friends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// In name order.
+    pub packages: Vec<ManifestPackage>,
+    /// The root project's requirements, dev-dependencies included.
+    pub requirements: BTreeMap<PackageName, String>,
+    /// Requirements that `gleam add` wrote into an existing manifest. It edits
+    /// the file with toml_edit, which renders them as `{ version = "..."}`
+    /// with no space before the closing brace.
+    pub added: BTreeSet<PackageName>,
+}
+
+/// One entry of `packages`. Only Hex packages are modelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestPackage {
+    pub name: PackageName,
+    pub version: Version,
+    pub build_tools: Vec<String>,
+    /// Names only, in name order.
+    pub requirements: Vec<PackageName>,
+    pub otp_app: String,
+    pub outer_checksum: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ManifestError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid manifest: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("invalid version `{version}` of {package}: {error}")]
+    InvalidVersion {
+        package: PackageName,
+        version: String,
+        error: String,
+    },
+    #[error("{package} comes from `{source_kind}`, only hex packages are supported")]
+    UnsupportedSource {
+        package: PackageName,
+        source_kind: String,
+    },
+    #[error("{package} {version} is not in the registry")]
+    MissingRelease {
+        package: PackageName,
+        version: Version,
+    },
+    #[error("the requirements of {package} {version} are unknown")]
+    UnknownRequirements {
+        package: PackageName,
+        version: Version,
+    },
+    #[error("the registry has no checksum for {package} {version}")]
+    MissingChecksum {
+        package: PackageName,
+        version: Version,
+    },
+}
+
+#[derive(Deserialize)]
+struct RawManifest {
+    #[serde(default)]
+    packages: Vec<RawPackage>,
+    #[serde(default)]
+    requirements: BTreeMap<PackageName, RawRequirement>,
+}
+
+#[derive(Deserialize)]
+struct RawPackage {
+    name: PackageName,
+    version: String,
+    build_tools: Vec<String>,
+    requirements: Vec<PackageName>,
+    otp_app: String,
+    source: String,
+    outer_checksum: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawRequirement {
+    version: Option<String>,
+}
+
+impl Manifest {
+    /// The manifest Gleam writes for `project` after resolving to `solution`,
+    /// with package metadata taken from the registry.
+    pub fn from_solution(
+        project: &Project,
+        registry: &Registry,
+        solution: &BTreeMap<PackageName, Version>,
+    ) -> Result<Self, ManifestError> {
+        let mut packages = Vec::new();
+        for (name, version) in solution {
+            if *name == project.name {
+                continue;
+            }
+            let release =
+                registry
+                    .release(name, version)
+                    .ok_or_else(|| ManifestError::MissingRelease {
+                        package: name.clone(),
+                        version: version.clone(),
+                    })?;
+            let requirements = release.requirements.as_ref().ok_or_else(|| {
+                ManifestError::UnknownRequirements {
+                    package: name.clone(),
+                    version: version.clone(),
+                }
+            })?;
+            let outer_checksum =
+                release
+                    .outer_checksum
+                    .clone()
+                    .ok_or_else(|| ManifestError::MissingChecksum {
+                        package: name.clone(),
+                        version: version.clone(),
+                    })?;
+            packages.push(ManifestPackage {
+                name: name.clone(),
+                version: version.clone(),
+                build_tools: if release.build_tools.is_empty() {
+                    vec!["gleam".into()]
+                } else {
+                    release.build_tools.clone()
+                },
+                requirements: requirements.keys().cloned().collect(),
+                otp_app: release.otp_app.clone().unwrap_or_else(|| name.clone()),
+                outer_checksum,
+            });
+        }
+
+        Ok(Self {
+            packages,
+            requirements: project
+                .requirements(true)
+                .into_iter()
+                .map(|(name, requirement)| (name, requirement.spec.as_str().to_string()))
+                .collect(),
+            added: BTreeSet::new(),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ManifestError> {
+        let source = std::fs::read_to_string(path).map_err(|source| ManifestError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::from_toml(&source)
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, ManifestError> {
+        let raw: RawManifest = toml::from_str(source)?;
+
+        let mut packages = Vec::new();
+        for package in raw.packages {
+            let (Some(outer_checksum), "hex") = (package.outer_checksum, package.source.as_str())
+            else {
+                return Err(ManifestError::UnsupportedSource {
+                    package: package.name,
+                    source_kind: package.source,
+                });
+            };
+            let version = Version::parse(&package.version).map_err(|error| {
+                ManifestError::InvalidVersion {
+                    package: package.name.clone(),
+                    version: package.version.clone(),
+                    error: error.to_string(),
+                }
+            })?;
+            packages.push(ManifestPackage {
+                name: package.name,
+                version,
+                build_tools: package.build_tools,
+                requirements: package.requirements,
+                otp_app: package.otp_app,
+                outer_checksum,
+            });
+        }
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut requirements = BTreeMap::new();
+        for (name, requirement) in raw.requirements {
+            let Some(version) = requirement.version else {
+                return Err(ManifestError::UnsupportedSource {
+                    package: name,
+                    source_kind: "path or git".into(),
+                });
+            };
+            let _ = requirements.insert(name, version);
+        }
+
+        Ok(Self {
+            packages,
+            requirements,
+            added: added_requirements(source),
+        })
+    }
+
+    pub fn package(&self, name: &str) -> Option<&ManifestPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    /// The manifest exactly as Gleam would write it.
+    pub fn to_toml(&self) -> String {
+        let mut buffer = String::new();
+        buffer.push_str("# This file was generated by Gleam\n");
+        buffer.push_str("# You typically do not need to edit this file\n\n");
+
+        buffer.push_str("packages = [\n");
+        for package in &self.packages {
+            let _ = writeln!(
+                buffer,
+                r#"  {{ name = "{}", version = "{}", build_tools = [{}], requirements = [{}], otp_app = "{}", source = "hex", outer_checksum = "{}" }},"#,
+                package.name,
+                package.version,
+                quoted_list(&package.build_tools),
+                quoted_list(&package.requirements),
+                package.otp_app,
+                package.outer_checksum,
+            );
+        }
+        buffer.push_str("]\n\n");
+
+        buffer.push_str("[requirements]\n");
+        for (name, version) in &self.requirements {
+            let space = if self.added.contains(name) { "" } else { " " };
+            let _ = writeln!(buffer, r#"{name} = {{ version = "{version}"{space}}}"#);
+        }
+        buffer
+    }
+}
+
+fn quoted_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("\"{item}\""))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// The toml parser forgets formatting, so look for toml_edit's spacing in the
+// text itself.
+fn added_requirements(source: &str) -> BTreeSet<PackageName> {
+    source
+        .lines()
+        .skip_while(|line| line.trim() != "[requirements]")
+        .filter(|line| line.trim_end().ends_with("\"}"))
+        .filter_map(|line| Some(line.split_once('=')?.0.trim().to_string()))
+        .collect()
+}
@@ -0,0 +1,125 @@
+//! Shrinking a fixture to the fewest packages, releases and requirements that
+//! still fail the same way, which is the first thing an upstream maintainer
+//! asks for.
+//!
+//! Each is removed in turn and the removal kept if the fixture still fails,
+//! until nothing more can go. The result is minimal in that no single
+//! removal keeps the failure, not the smallest possible.
+
+use hexpm::version::Version;
+
+use crate::{
+    fixture::Fixture,
+    registry::{Registry, Release},
+    PackageName,
+};
+
+pub fn minimise(fixture: &Fixture, mut still_fails: impl FnMut(&Fixture) -> bool) -> Fixture {
+    let mut current = fixture.clone();
+    loop {
+        let before = size(&current);
+
+        let packages: Vec<_> = current
+            .registry
+            .packages()
+            .map(|(name, _)| name.clone())
+            .filter(|name| *name != current.root)
+            .collect();
+        for package in packages {
+            let candidate = rebuild(&current, |name, _, release| {
+                (*name != package).then(|| release.clone())
+            });
+            try_candidate(&mut current, candidate, &mut still_fails);
+        }
+
+        let removable: Vec<_> = releases(&current)
+            .filter(|(name, version)| *name != current.root || *version != current.root_version)
+            .collect();
+        for (package, removed) in removable {
+            let candidate = rebuild(&current, |name, version, release| {
+                (*name != package || *version != removed).then(|| release.clone())
+            });
+            try_candidate(&mut current, candidate, &mut still_fails);
+        }
+
+        for (package, version) in releases(&current).collect::<Vec<_>>() {
+            let dependencies: Vec<_> = current
+                .registry
+                .release(&package, &version)
+                .and_then(|release| release.requirements.as_ref())
+                .into_iter()
+                .flat_map(|requirements| requirements.keys().cloned())
+                .collect();
+            for dependency in dependencies {
+                let candidate = rebuild(&current, |name, other, release| {
+                    let mut release = release.clone();
+                    if *name == package && *other == version {
+                        if let Some(requirements) = &mut release.requirements {
+                            let _ = requirements.remove(&dependency);
+                        }
+                    }
+                    Some(release)
+                });
+                try_candidate(&mut current, candidate, &mut still_fails);
+            }
+        }
+
+        if size(&current) == before {
+            return current;
+        }
+    }
+}
+
+fn try_candidate(
+    current: &mut Fixture,
+    candidate: Fixture,
+    still_fails: &mut impl FnMut(&Fixture) -> bool,
+) {
+    if still_fails(&candidate) {
+        *current = candidate;
+    }
+}
+
+fn releases(fixture: &Fixture) -> impl Iterator<Item = (PackageName, Version)> + '_ {
+    fixture.registry.packages().flat_map(|(name, releases)| {
+        releases
+            .keys()
+            .map(move |version| (name.clone(), version.clone()))
+    })
+}
+
+// The fixture with each release replaced by what `keep` returns for it.
+fn rebuild(
+    fixture: &Fixture,
+    keep: impl Fn(&PackageName, &Version, &Release) -> Option<Release>,
+) -> Fixture {
+    let mut registry = Registry::new();
+    for (name, releases) in fixture.registry.packages() {
+        for (version, release) in releases {
+            if let Some(release) = keep(name, version, release) {
+                registry.insert(name.clone(), version.clone(), release);
+            }
+        }
+    }
+    Fixture {
+        root: fixture.root.clone(),
+        root_version: fixture.root_version.clone(),
+        registry,
+    }
+}
+
+// Packages, releases and requirements, so any removal makes it smaller.
+fn size(fixture: &Fixture) -> (usize, usize, usize) {
+    let requirements = fixture
+        .registry
+        .packages()
+        .flat_map(|(_, releases)| releases.values())
+        .filter_map(|release| release.requirements.as_ref())
+        .map(|requirements| requirements.len())
+        .sum();
+    (
+        fixture.registry.package_count(),
+        fixture.registry.release_count(),
+        requirements,
+    )
+}
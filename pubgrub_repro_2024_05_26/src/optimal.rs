@@ -0,0 +1,134 @@
+//! The newest solution a registry allows, to tell whether pubgrub settles for
+//! older versions than it needs to.
+//!
+//! Solutions are ranked lexicographically: the root's direct dependencies in
+//! name order, then every other package in name order, each compared by
+//! version. A solution without a package ranks below one with any version of
+//! it.
+
+use std::{cmp::Ordering, collections::BTreeSet, fmt, ops::ControlFlow};
+
+use hexpm::version::Version;
+
+use crate::{
+    enumerate::{self, Solution},
+    registry::Registry,
+    PackageName,
+};
+
+/// The best solution seen before stopping.
+#[derive(Debug, Clone)]
+pub struct Newest {
+    /// `None` when there is no solution.
+    pub solution: Option<Solution>,
+    /// How many solutions were compared.
+    pub compared: u64,
+    /// False when the search stopped at the cap, so a newer one may exist.
+    pub complete: bool,
+}
+
+/// A package pubgrub resolved to something older than the newest solution
+/// has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stale {
+    pub package: PackageName,
+    pub chosen: Version,
+    pub newest: Version,
+    /// Whether the root requires it directly.
+    pub direct: bool,
+}
+
+/// The order packages are compared in: the root's direct dependencies, then
+/// the rest of the registry, each in name order.
+pub fn priority(registry: &Registry, root: &str, root_version: &Version) -> Vec<PackageName> {
+    let direct: BTreeSet<_> = registry
+        .release(root, root_version)
+        .and_then(|release| release.requirements.as_ref())
+        .into_iter()
+        .flat_map(|requirements| requirements.keys().cloned())
+        .collect();
+    let rest = registry
+        .packages()
+        .map(|(name, _)| name)
+        .filter(|name| *name != root && !direct.contains(*name))
+        .cloned();
+    direct.iter().cloned().chain(rest).collect()
+}
+
+/// Ranks two solutions by `priority`.
+pub fn compare(priority: &[PackageName], a: &Solution, b: &Solution) -> Ordering {
+    priority
+        .iter()
+        .map(|name| a.get(name).cmp(&b.get(name)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// The newest solution among the first `cap` found.
+pub fn newest(registry: &Registry, root: &str, root_version: &Version, cap: u64) -> Newest {
+    let priority = priority(registry, root, root_version);
+    let mut best: Option<Solution> = None;
+    let mut compared = 0;
+    let flow = enumerate::for_each_solution(registry, root, root_version, |solution| {
+        if compared == cap {
+            return ControlFlow::Break(());
+        }
+        compared += 1;
+        let newer = best
+            .as_ref()
+            .is_none_or(|best| compare(&priority, solution, best).is_gt());
+        if newer {
+            best = Some(solution.clone());
+        }
+        ControlFlow::Continue(())
+    });
+    Newest {
+        solution: best,
+        compared,
+        complete: flow.is_continue(),
+    }
+}
+
+/// Every package `chosen` has at an older version than `newest`, in priority
+/// order.
+pub fn stale(
+    registry: &Registry,
+    root: &str,
+    root_version: &Version,
+    chosen: &Solution,
+    newest: &Solution,
+) -> Vec<Stale> {
+    let direct = registry
+        .release(root, root_version)
+        .and_then(|release| release.requirements.as_ref());
+    priority(registry, root, root_version)
+        .into_iter()
+        .filter_map(|package| {
+            let chosen = chosen.get(&package)?;
+            let newest = newest.get(&package)?;
+            if chosen >= newest {
+                return None;
+            }
+            Some(Stale {
+                direct: direct.is_some_and(|direct| direct.contains_key(&package)),
+                chosen: chosen.clone(),
+                newest: newest.clone(),
+                package,
+            })
+        })
+        .collect()
+}
+
+impl fmt::Display for Stale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} could be {}",
+            self.package, self.chosen, self.newest
+        )?;
+        if self.direct {
+            write!(f, " (direct)")?;
+        }
+        Ok(())
+    }
+}
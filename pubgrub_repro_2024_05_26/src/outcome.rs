@@ -0,0 +1,167 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+use hexpm::version::Version;
+use pubgrub::{
+    error::PubGrubError,
+    report::{DefaultStringReporter, DerivationTree, Reporter},
+    solver::DependencyProvider,
+};
+
+use crate::{budget::BudgetExceeded, PackageName};
+
+/// How a single resolution ended. Panics inside pubgrub are caught and kept
+/// as an outcome of their own, as that is what these repros are about.
+#[derive(Debug)]
+pub enum Outcome {
+    Solved(BTreeMap<PackageName, Version>),
+    NoSolution(Box<DerivationTree<PackageName, Version>>),
+    /// No solution, from a backend that cannot say why.
+    Unsatisfiable,
+    Failed(String),
+    Panicked(String),
+    /// A [`BudgetedDependencyProvider`](crate::budget::BudgetedDependencyProvider)
+    /// stopped the resolution.
+    TimedOut(BudgetExceeded),
+}
+
+pub fn resolve(
+    provider: &impl DependencyProvider<PackageName, Version>,
+    root: &str,
+    root_version: &Version,
+) -> Outcome {
+    let span = tracing::info_span!("resolve", root, %root_version);
+    let _entered = span.enter();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        match pubgrub::solver::resolve(provider, root.to_string(), root_version.clone()) {
+            Ok(solution) => Outcome::Solved(solution.into_iter().collect()),
+            Err(PubGrubError::NoSolution(tree)) => Outcome::NoSolution(Box::new(tree)),
+            Err(error) => match budget_exceeded(&error) {
+                Some(exceeded) => Outcome::TimedOut(exceeded.clone()),
+                None => Outcome::Failed(describe_error(&error)),
+            },
+        }
+    }));
+
+    let outcome =
+        result.unwrap_or_else(|payload| Outcome::Panicked(panic_message(payload.as_ref())));
+    match &outcome {
+        Outcome::Panicked(message) => tracing::error!(message, "pubgrub panicked"),
+        outcome => tracing::info!(outcome = outcome.kind(), "finished"),
+    }
+    outcome
+}
+
+impl Outcome {
+    pub fn is_solved(&self) -> bool {
+        matches!(self, Outcome::Solved(_))
+    }
+
+    /// A short label for the kind of outcome.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Outcome::Solved(_) => "solved",
+            Outcome::NoSolution(_) | Outcome::Unsatisfiable => "no solution",
+            Outcome::Failed(_) => "failed",
+            Outcome::Panicked(_) => "panicked",
+            Outcome::TimedOut(_) => "timed out",
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Solved(solution) => {
+                writeln!(f, "solved:")?;
+                for (name, version) in solution {
+                    writeln!(f, "  {name} {version}")?;
+                }
+                Ok(())
+            }
+            Outcome::NoSolution(tree) => {
+                writeln!(f, "no solution:")?;
+                writeln!(f, "{}", DefaultStringReporter::report(tree))
+            }
+            Outcome::Unsatisfiable => writeln!(f, "no solution"),
+            Outcome::Failed(message) => writeln!(f, "failed: {message}"),
+            Outcome::Panicked(message) => writeln!(f, "panicked: {message}"),
+            Outcome::TimedOut(exceeded) => writeln!(
+                f,
+                "timed out after {} steps: {}",
+                exceeded.steps, exceeded.reason
+            ),
+        }
+    }
+}
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` without printing the message of any panic on this thread, for
+/// resolutions expected to panic. Other threads, like parallel tests, still
+/// print theirs, and the setting is restored however `f` returns.
+pub fn quietly<T>(f: impl FnOnce() -> T) -> T {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(ToString::to_string);
+            PANIC_LOCATION.with_borrow_mut(|recorded| *recorded = location);
+            if !QUIET.get() {
+                default(info);
+            }
+        }));
+    });
+
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            QUIET.set(self.0);
+        }
+    }
+    let _restore = Restore(QUIET.replace(true));
+    f()
+}
+
+/// Where the last panic on this thread happened, once [`quietly`] has been
+/// used.
+pub fn panic_location() -> Option<String> {
+    PANIC_LOCATION.with_borrow(Clone::clone)
+}
+
+fn budget_exceeded(error: &PubGrubError<PackageName, Version>) -> Option<&BudgetExceeded> {
+    match error {
+        PubGrubError::ErrorRetrievingDependencies { source, .. }
+        | PubGrubError::ErrorChoosingPackageVersion(source)
+        | PubGrubError::ErrorInShouldCancel(source) => source.downcast_ref(),
+        _ => None,
+    }
+}
+
+fn describe_error(error: &PubGrubError<PackageName, Version>) -> String {
+    match error {
+        PubGrubError::ErrorRetrievingDependencies { source, .. }
+        | PubGrubError::ErrorChoosingPackageVersion(source)
+        | PubGrubError::ErrorInShouldCancel(source) => format!("{error}: {source}"),
+        _ => error.to_string(),
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<non-string panic payload>".into()
+    }
+}
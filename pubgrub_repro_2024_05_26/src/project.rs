@@ -0,0 +1,177 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use hexpm::version::Version;
+use serde::Deserialize;
+
+use crate::{
+    fixture::Fixture,
+    registry::{Registry, Release, Requirement},
+    PackageName,
+};
+
+/// The root project as described by its `gleam.toml`, with dependencies and
+/// dev-dependencies kept apart so either set can be resolved.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub name: PackageName,
+    pub version: Version,
+    pub dependencies: BTreeMap<PackageName, Requirement>,
+    pub dev_dependencies: BTreeMap<PackageName, Requirement>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid gleam.toml: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("invalid project version `{version}`: {error}")]
+    InvalidVersion { version: String, error: String },
+    #[error("invalid requirement `{requirement}` on {dependency}: {error}")]
+    InvalidRequirement {
+        dependency: PackageName,
+        requirement: String,
+        error: String,
+    },
+    #[error("{0} is in both [dependencies] and [dev-dependencies]")]
+    DuplicateDependency(PackageName),
+}
+
+#[derive(Deserialize)]
+struct RawProject {
+    name: PackageName,
+    #[serde(default = "default_version")]
+    version: String,
+    #[serde(default)]
+    dependencies: BTreeMap<PackageName, String>,
+    #[serde(default, rename = "dev-dependencies")]
+    dev_dependencies: BTreeMap<PackageName, String>,
+}
+
+fn default_version() -> String {
+    "0.0.0".into()
+}
+
+impl Project {
+    pub fn load(path: &Path) -> Result<Self, ProjectError> {
+        let source = std::fs::read_to_string(path).map_err(|source| ProjectError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::from_toml(&source)
+    }
+
+    /// Reads the fields of a `gleam.toml` that matter for resolution and
+    /// ignores the rest.
+    pub fn from_toml(source: &str) -> Result<Self, ProjectError> {
+        let raw: RawProject = toml::from_str(source)?;
+        let version =
+            Version::parse(&raw.version).map_err(|error| ProjectError::InvalidVersion {
+                version: raw.version.clone(),
+                error: error.to_string(),
+            })?;
+        if let Some(name) = raw
+            .dependencies
+            .keys()
+            .find(|name| raw.dev_dependencies.contains_key(*name))
+        {
+            return Err(ProjectError::DuplicateDependency(name.clone()));
+        }
+
+        Ok(Self {
+            name: raw.name,
+            version,
+            dependencies: parse_requirements(raw.dependencies)?,
+            dev_dependencies: parse_requirements(raw.dev_dependencies)?,
+        })
+    }
+
+    /// What the root requires, with or without its dev-dependencies.
+    pub fn requirements(&self, include_dev: bool) -> BTreeMap<PackageName, Requirement> {
+        let mut requirements = self.dependencies.clone();
+        if include_dev {
+            requirements.extend(self.dev_dependencies.clone());
+        }
+        requirements
+    }
+
+    /// A fixture resolving this project against `registry`. Any release the
+    /// registry already has for the project is replaced.
+    pub fn fixture(&self, registry: &Registry, include_dev: bool) -> Fixture {
+        let mut registry = registry.clone();
+        registry.insert(
+            self.name.clone(),
+            self.version.clone(),
+            Release {
+                requirements: Some(self.requirements(include_dev)),
+                ..Release::default()
+            },
+        );
+        Fixture {
+            root: self.name.clone(),
+            root_version: self.version.clone(),
+            registry,
+        }
+    }
+
+    /// Packages in `solution` that only dev-dependencies lead to, found by
+    /// following the requirements of each chosen version from the root's
+    /// regular dependencies.
+    pub fn dev_only(
+        &self,
+        registry: &Registry,
+        solution: &BTreeMap<PackageName, Version>,
+    ) -> BTreeSet<PackageName> {
+        let reachable = reachable(registry, solution, self.dependencies.keys());
+        solution
+            .keys()
+            .filter(|name| **name != self.name && !reachable.contains(*name))
+            .cloned()
+            .collect()
+    }
+}
+
+fn parse_requirements(
+    raw: BTreeMap<PackageName, String>,
+) -> Result<BTreeMap<PackageName, Requirement>, ProjectError> {
+    raw.into_iter()
+        .map(
+            |(dependency, requirement)| match Requirement::parse(&requirement) {
+                Ok(parsed) => Ok((dependency, parsed)),
+                Err(error) => Err(ProjectError::InvalidRequirement {
+                    dependency,
+                    requirement,
+                    error: error.to_string(),
+                }),
+            },
+        )
+        .collect()
+}
+
+fn reachable<'a>(
+    registry: &Registry,
+    solution: &BTreeMap<PackageName, Version>,
+    roots: impl Iterator<Item = &'a PackageName>,
+) -> BTreeSet<PackageName> {
+    let mut seen = BTreeSet::new();
+    let mut stack: Vec<_> = roots.cloned().collect();
+    while let Some(name) = stack.pop() {
+        let Some(version) = solution.get(&name) else {
+            continue;
+        };
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        let requirements = registry
+            .release(&name, version)
+            .and_then(|release| release.requirements.as_ref());
+        stack.extend(requirements.into_iter().flat_map(|r| r.keys().cloned()));
+    }
+    seen
+}
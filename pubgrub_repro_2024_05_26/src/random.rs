@@ -0,0 +1,31 @@
+/// A xorshift generator: the point is replaying a seed, not quality.
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // Xorshift never leaves zero, and nearby seeds should not start alike.
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// True with probability `chance`, between 0 and 1.
+    pub fn chance(&mut self, chance: f64) -> bool {
+        chance > 0.0 && (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= chance
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use hexpm::version::{Range, Version};
+use pubgrub::solver::DependencyConstraints;
+
+use crate::{PackageName, PubgrubRange};
+
+/// Every known release of every package, as a dependency provider would see
+/// them. Providers are built from this so they can be compared like for like.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    packages: BTreeMap<PackageName, BTreeMap<Version, Release>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Release {
+    /// `None` when the registry lists the version but not what it requires,
+    /// like wisp in the #3201 repro. Providers fail `get_dependencies` for
+    /// these, just as `Issue3201DependencyProvider` does.
+    pub requirements: Option<BTreeMap<PackageName, Requirement>>,
+    /// Empty when not recorded; manifests then assume `["gleam"]`.
+    pub build_tools: Vec<String>,
+    /// Defaults to the package name.
+    pub otp_app: Option<String>,
+    /// Hex's checksum of the release tarball, as written in manifest.toml.
+    pub outer_checksum: Option<String>,
+}
+
+/// A requirement as written in the registry, along with the range pubgrub
+/// sees once it has been converted.
+#[derive(Debug, Clone)]
+pub struct Requirement {
+    pub spec: Range,
+    pub range: PubgrubRange,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: PackageName, version: Version, release: Release) {
+        let _ = self
+            .packages
+            .entry(name)
+            .or_default()
+            .insert(version, release);
+    }
+
+    /// Packages in name order, each with its releases oldest first.
+    pub fn packages(&self) -> impl Iterator<Item = (&PackageName, &BTreeMap<Version, Release>)> {
+        self.packages.iter()
+    }
+
+    pub fn releases(&self, name: &str) -> Option<&BTreeMap<Version, Release>> {
+        self.packages.get(name)
+    }
+
+    pub fn release(&self, name: &str, version: &Version) -> Option<&Release> {
+        self.packages.get(name)?.get(version)
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn release_count(&self) -> usize {
+        self.packages.values().map(BTreeMap::len).sum()
+    }
+}
+
+impl Release {
+    pub fn dependency_constraints(&self) -> Option<DependencyConstraints<PackageName, Version>> {
+        let requirements = self.requirements.as_ref()?;
+        Some(
+            requirements
+                .iter()
+                .map(|(name, requirement)| (name.clone(), requirement.range.clone()))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RequirementError {
+    #[error("the requirement is empty, use `*` to accept any version")]
+    Empty,
+    #[error("`{found}` is not supported, {hint}")]
+    Unsupported { found: String, hint: &'static str },
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl Requirement {
+    /// Accepts everything Hex and gleam.toml do: `*`, `~> 1.2`, `~> 1.2.3`,
+    /// bare and `==` versions, `!=`, comparisons, and `and`/`or` combinations
+    /// of them. Syntax from other ecosystems is rejected with a pointer to the
+    /// Hex equivalent rather than a parser error.
+    pub fn parse(spec: &str) -> Result<Self, RequirementError> {
+        let trimmed = spec.trim();
+        if trimmed.is_empty() {
+            return Err(RequirementError::Empty);
+        }
+        // hexpm has no syntax for this, so it never reaches the parser.
+        if trimmed == "*" {
+            return Ok(Self {
+                spec: Range::new(trimmed.to_string()),
+                range: PubgrubRange::any(),
+            });
+        }
+        reject_unsupported(trimmed)?;
+
+        let spec = Range::new(trimmed.to_string());
+        let range = spec
+            .to_pubgrub()
+            .map_err(|error| RequirementError::Invalid(error.to_string()))?;
+        Ok(Self { spec, range })
+    }
+}
+
+fn reject_unsupported(spec: &str) -> Result<(), RequirementError> {
+    let unsupported = |found: &str, hint| {
+        Err(RequirementError::Unsupported {
+            found: found.to_string(),
+            hint,
+        })
+    };
+
+    for (found, hint) in [
+        ("||", "use `or`"),
+        ("&&", "use `and`"),
+        (",", "join conditions with `and`"),
+        ("^", "use `~>`, e.g. `~> 1.2` for any 1.x from 1.2.0"),
+    ] {
+        if spec.contains(found) {
+            return unsupported(found, hint);
+        }
+    }
+
+    // Operators may be written apart from their operand, `~> 1.2`, or against
+    // it, `~>1.2`.
+    let mut pending_operator = "";
+    for word in spec.split_whitespace() {
+        let operand = word.trim_start_matches(['<', '>', '=', '!', '~']);
+        let operator = match &word[..word.len() - operand.len()] {
+            "" => std::mem::take(&mut pending_operator),
+            operator => operator,
+        };
+        if operand.is_empty() {
+            pending_operator = operator;
+            continue;
+        }
+        match operator {
+            "=" => return unsupported(operator, "use `==` or the bare version"),
+            "~" => return unsupported(operator, "use `~>`"),
+            _ => {}
+        }
+        if operand == "*" {
+            return unsupported(word, "`*` has to be the whole requirement");
+        }
+
+        let release = operand.split(['-', '+']).next().unwrap_or_default();
+        let components: Vec<_> = release.split('.').collect();
+        if components.len() > 1 && components.iter().any(|c| matches!(*c, "x" | "X" | "*")) {
+            return unsupported(word, "use `~>` instead of wildcard components");
+        }
+        let is_version = components
+            .iter()
+            .all(|c| !c.is_empty() && c.bytes().all(|b| b.is_ascii_digit()));
+        if is_version && components.len() < 3 && operator != "~>" {
+            return unsupported(
+                word,
+                "versions need major, minor and patch components, only `~>` takes two",
+            );
+        }
+    }
+
+    Ok(())
+}
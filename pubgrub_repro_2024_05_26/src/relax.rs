@@ -0,0 +1,268 @@
+//! Suggestions for which requirements to loosen when a registry has no
+//! solution, smallest change first.
+
+use std::fmt;
+
+use hexpm::version::Version;
+
+use crate::{
+    budget::{Budget, BudgetExceeded, BudgetedDependencyProvider},
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+    registry::{Registry, Requirement},
+    sat::{self, SatError},
+    PackageName, PubgrubRange,
+};
+
+/// Widening one bound of one requirement just far enough to admit the next
+/// release beyond it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relaxation {
+    pub package: PackageName,
+    pub version: Version,
+    pub dependency: PackageName,
+    pub requirement: String,
+    pub bound: Bound,
+    pub admits: Version,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Lower,
+    Upper,
+}
+
+/// A set of relaxations that makes the fixture solvable.
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub relaxations: Vec<Relaxation>,
+    /// The panic message when pubgrub panics on the relaxed registry rather
+    /// than finding the solution.
+    pub panicked: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RelaxError {
+    #[error("the fixture already resolves")]
+    Solvable,
+    #[error("only a fixture with no solution can be relaxed, but pubgrub {0}")]
+    Baseline(String),
+    #[error("deciding whether a relaxation resolves ran out of budget: {0}")]
+    TimedOut(BudgetExceeded),
+}
+
+/// Every minimal set of at most `max_changes` relaxations that makes the
+/// fixture solvable, fewest changes first. pubgrub must find no solution for
+/// the fixture itself. Each attempt gets its own `budget`, and is decided by
+/// the SAT backend, so that a pubgrub panic on a relaxed registry neither
+/// hides an answer nor passes for one.
+///
+/// This tries every combination of every requirement in the registry, so it
+/// is only practical for small registries and small `max_changes`.
+pub fn suggest(
+    fixture: &Fixture,
+    max_changes: usize,
+    budget: Budget,
+) -> Result<Vec<Suggestion>, RelaxError> {
+    match pubgrub(&fixture.registry, fixture, budget) {
+        Outcome::NoSolution(_) => {}
+        Outcome::Solved(_) => return Err(RelaxError::Solvable),
+        outcome => return Err(RelaxError::Baseline(outcome.to_string().trim_end().into())),
+    }
+
+    let candidates = candidates(fixture);
+    let mut found: Vec<Vec<usize>> = Vec::new();
+    for changes in 1..=max_changes {
+        for combination in combinations(candidates.len(), changes) {
+            // Only minimal sets: skip anything containing a smaller answer.
+            if found
+                .iter()
+                .any(|smaller| smaller.iter().all(|index| combination.contains(index)))
+            {
+                continue;
+            }
+            let chosen = combination.iter().map(|&index| &candidates[index]);
+            let registry = relaxed(fixture, chosen);
+            match sat::resolve(&registry, &fixture.root, &fixture.root_version, budget) {
+                Ok(_) => found.push(combination),
+                Err(SatError::NoSolution) => {}
+                Err(SatError::BudgetExceeded(exceeded)) => {
+                    return Err(RelaxError::TimedOut(exceeded))
+                }
+            }
+        }
+    }
+    Ok(found
+        .into_iter()
+        .map(|combination| {
+            let relaxations: Vec<_> = combination
+                .into_iter()
+                .map(|index| candidates[index].clone())
+                .collect();
+            let registry = relaxed(fixture, &relaxations);
+            let panicked = match pubgrub(&registry, fixture, budget) {
+                Outcome::Panicked(message) => Some(message),
+                _ => None,
+            };
+            Suggestion {
+                relaxations,
+                panicked,
+            }
+        })
+        .collect())
+}
+
+fn candidates(fixture: &Fixture) -> Vec<Relaxation> {
+    let mut candidates = Vec::new();
+    for (package, releases) in fixture.registry.packages() {
+        for (version, release) in releases {
+            for (dependency, requirement) in release.requirements.iter().flatten() {
+                let available: Vec<_> = fixture
+                    .registry
+                    .releases(dependency)
+                    .into_iter()
+                    .flat_map(|releases| releases.keys())
+                    .collect();
+                for bound in [Bound::Lower, Bound::Upper] {
+                    if let Some(admits) = next_beyond(&requirement.range, &available, bound) {
+                        candidates.push(Relaxation {
+                            package: package.clone(),
+                            version: version.clone(),
+                            dependency: dependency.clone(),
+                            requirement: requirement.spec.as_str().to_string(),
+                            bound,
+                            admits: admits.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    candidates
+}
+
+// The closest available version outside `range` on the given side of all the
+// versions it admits. `available` is in ascending order.
+fn next_beyond<'a>(
+    range: &PubgrubRange,
+    available: &[&'a Version],
+    bound: Bound,
+) -> Option<&'a Version> {
+    let admitted: Vec<_> = available
+        .iter()
+        .filter(|version| range.contains(version))
+        .collect();
+    let (lowest, highest) = match (admitted.first(), admitted.last()) {
+        (Some(lowest), Some(highest)) => (**lowest, **highest),
+        // Nothing admitted, so measure from where the range starts.
+        _ => {
+            let start = range.lowest_version()?;
+            return match bound {
+                Bound::Lower => available.iter().rev().find(|version| ***version < start),
+                Bound::Upper => available.iter().find(|version| ***version >= start),
+            }
+            .copied();
+        }
+    };
+    match bound {
+        Bound::Lower => available.iter().rev().find(|version| **version < lowest),
+        Bound::Upper => available.iter().find(|version| **version > highest),
+    }
+    .copied()
+}
+
+/// The fixture's registry with each relaxation's requirement widened, as a
+/// Hex requirement that can go back into a gleam.toml.
+pub fn relaxed<'a>(
+    fixture: &Fixture,
+    relaxations: impl IntoIterator<Item = &'a Relaxation>,
+) -> Registry {
+    let mut registry = fixture.registry.clone();
+    for relaxation in relaxations {
+        let Some(mut release) = registry
+            .release(&relaxation.package, &relaxation.version)
+            .cloned()
+        else {
+            continue;
+        };
+        if let Some(requirement) = release
+            .requirements
+            .as_mut()
+            .and_then(|requirements| requirements.get_mut(&relaxation.dependency))
+        {
+            let spec = format!(
+                "{} or {}",
+                requirement.spec.as_str(),
+                only(
+                    &fixture.registry,
+                    &relaxation.dependency,
+                    &relaxation.admits
+                )
+            );
+            *requirement = Requirement::parse(&spec).expect("generated requirement");
+        }
+        registry.insert(
+            relaxation.package.clone(),
+            relaxation.version.clone(),
+            release,
+        );
+    }
+
+    registry
+}
+
+fn pubgrub(registry: &Registry, fixture: &Fixture, budget: Budget) -> Outcome {
+    let provider = BudgetedDependencyProvider::new(
+        InternedDependencyProvider::from_registry(registry),
+        budget,
+    );
+    outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+}
+
+// A Hex requirement admitting `version` and no other release. `== version`
+// would also take in some pre-releases and the next patch release.
+fn only(registry: &Registry, package: &str, version: &Version) -> String {
+    let following = registry
+        .releases(package)
+        .and_then(|releases| releases.keys().find(|next| *next > version));
+    match following {
+        Some(following) => format!(">= {version} and < {following}"),
+        None => format!(">= {version}"),
+    }
+}
+
+// Every `size`-element subset of `0..len`, in lexicographic order.
+fn combinations(len: usize, size: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut next = (size <= len).then(|| (0..size).collect::<Vec<_>>());
+    std::iter::from_fn(move || {
+        let current = next.take()?;
+        let mut following = current.clone();
+        if let Some(position) = (0..size).rev().find(|&i| following[i] < len - size + i) {
+            following[position] += 1;
+            for i in position + 1..size {
+                following[i] = following[i - 1] + 1;
+            }
+            next = Some(following);
+        }
+        Some(current)
+    })
+}
+
+impl fmt::Display for Relaxation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "loosen the {} bound of {} {}'s requirement on {} ({}) to admit {}",
+            match self.bound {
+                Bound::Lower => "lower",
+                Bound::Upper => "upper",
+            },
+            self.package,
+            self.version,
+            self.dependency,
+            self.requirement,
+            self.admits,
+        )
+    }
+}
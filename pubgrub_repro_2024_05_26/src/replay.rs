@@ -0,0 +1,228 @@
+//! Recording the decisions pubgrub makes and forcing them again, so a
+//! particular internal state, like the one that reaches `add_derivation`
+//! after a decision in #3201, can be reproduced however the version choice
+//! heuristic changes.
+//!
+//! Decisions are saved one per line as `package version`, or `package none`
+//! where no version was offered. Lines starting with `#` are comments.
+
+use std::{
+    borrow::Borrow,
+    cell::{Cell, RefCell},
+    error::Error as StdError,
+    fmt::{self, Write},
+};
+
+use hexpm::version::Version;
+use pubgrub::solver::{Dependencies, DependencyProvider};
+
+use crate::{PackageName, PubgrubRange};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub package: PackageName,
+    /// `None` when no version of the package was offered.
+    pub version: Option<Version>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ReplayError {
+    #[error("decision {step}: expected to decide {expected}, but pubgrub offered only {}", .candidates.join(", "))]
+    Diverged {
+        step: usize,
+        expected: Decision,
+        candidates: Vec<String>,
+    },
+    #[error("decision {step}: recorded {expected}, which is outside the range {range}")]
+    OutOfRange {
+        step: usize,
+        expected: Decision,
+        range: String,
+    },
+    #[error("decision {step}: pubgrub asked for another decision, but only {} were recorded", .step - 1)]
+    Exhausted { step: usize },
+    #[error("line {line}: expected `package version` or `package none`, found `{text}`")]
+    InvalidLine { line: usize, text: String },
+}
+
+/// Wraps a provider and remembers every decision it makes.
+pub struct RecordingDependencyProvider<DP> {
+    inner: DP,
+    decisions: RefCell<Vec<Decision>>,
+}
+
+/// Answers `choose_package_version` from a recorded sequence instead of the
+/// wrapped provider, which is still asked for dependencies. Any difference
+/// from the recording fails the resolution with a [`ReplayError`].
+pub struct ReplayDependencyProvider<DP> {
+    inner: DP,
+    decisions: Vec<Decision>,
+    replayed: Cell<usize>,
+}
+
+impl<DP> RecordingDependencyProvider<DP> {
+    pub fn new(inner: DP) -> Self {
+        Self {
+            inner,
+            decisions: RefCell::default(),
+        }
+    }
+
+    pub fn decisions(&self) -> Vec<Decision> {
+        self.decisions.borrow().clone()
+    }
+}
+
+impl<DP> ReplayDependencyProvider<DP> {
+    pub fn new(inner: DP, decisions: Vec<Decision>) -> Self {
+        Self {
+            inner,
+            decisions,
+            replayed: Cell::new(0),
+        }
+    }
+
+    /// How many decisions have been replayed so far.
+    pub fn replayed(&self) -> usize {
+        self.replayed.get()
+    }
+}
+
+impl<DP> DependencyProvider<PackageName, Version> for RecordingDependencyProvider<DP>
+where
+    DP: DependencyProvider<PackageName, Version>,
+{
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn StdError>> {
+        let (package, version) = self.inner.choose_package_version(potential_packages)?;
+        self.decisions.borrow_mut().push(Decision {
+            package: package.borrow().clone(),
+            version: version.clone(),
+        });
+        Ok((package, version))
+    }
+
+    fn get_dependencies(
+        &self,
+        name: &PackageName,
+        version: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn StdError>> {
+        self.inner.get_dependencies(name, version)
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn StdError>> {
+        self.inner.should_cancel()
+    }
+}
+
+impl<DP> DependencyProvider<PackageName, Version> for ReplayDependencyProvider<DP>
+where
+    DP: DependencyProvider<PackageName, Version>,
+{
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn StdError>> {
+        let step = self.replayed.get() + 1;
+        let Some(expected) = self.decisions.get(step - 1) else {
+            tracing::error!(step, "replay exhausted");
+            return Err(Box::new(ReplayError::Exhausted { step }));
+        };
+
+        let mut candidates = Vec::new();
+        for (package, range) in potential_packages {
+            if *package.borrow() != expected.package {
+                candidates.push(format!("{} {}", package.borrow(), range.borrow()));
+                continue;
+            }
+            if let Some(version) = &expected.version {
+                if !range.borrow().contains(version) {
+                    let error = ReplayError::OutOfRange {
+                        step,
+                        expected: expected.clone(),
+                        range: range.borrow().to_string(),
+                    };
+                    tracing::error!(%error, "replay diverged");
+                    return Err(Box::new(error));
+                }
+            }
+            self.replayed.set(step);
+            return Ok((package, expected.version.clone()));
+        }
+
+        let error = ReplayError::Diverged {
+            step,
+            expected: expected.clone(),
+            candidates,
+        };
+        tracing::error!(%error, "replay diverged");
+        Err(Box::new(error))
+    }
+
+    fn get_dependencies(
+        &self,
+        name: &PackageName,
+        version: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn StdError>> {
+        self.inner.get_dependencies(name, version)
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn StdError>> {
+        self.inner.should_cancel()
+    }
+}
+
+/// Reads decisions in the format [`write`] produces.
+pub fn parse(source: &str) -> Result<Vec<Decision>, ReplayError> {
+    let mut decisions = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || ReplayError::InvalidLine {
+            line: index + 1,
+            text: line.to_string(),
+        };
+        let mut words = line.split_whitespace();
+        let (Some(package), Some(version), None) = (words.next(), words.next(), words.next())
+        else {
+            return Err(invalid());
+        };
+        let version = match version {
+            "none" => None,
+            version => Some(Version::parse(version).map_err(|_| invalid())?),
+        };
+        decisions.push(Decision {
+            package: package.to_string(),
+            version,
+        });
+    }
+    Ok(decisions)
+}
+
+pub fn write(decisions: &[Decision]) -> String {
+    let mut out = String::new();
+    for decision in decisions {
+        match &decision.version {
+            Some(version) => {
+                let _ = writeln!(out, "{} {version}", decision.package);
+            }
+            None => {
+                let _ = writeln!(out, "{} none", decision.package);
+            }
+        }
+    }
+    out
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{} {version}", self.package),
+            None => write!(f, "{} with no version", self.package),
+        }
+    }
+}
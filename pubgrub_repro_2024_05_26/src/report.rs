@@ -0,0 +1,315 @@
+//! A single self-contained HTML page describing one resolution, to attach to
+//! tickets for people who won't run cargo.
+//!
+//! The page works offline: styles are inline, and the graph folds with
+//! `<details>` rather than JavaScript.
+
+use std::{collections::HashSet, fmt::Write};
+
+use pubgrub::report::{DefaultStringReporter, Reporter};
+
+use crate::{
+    diff::Resolution,
+    fixture::Fixture,
+    graph::{self, Graph},
+    outcome::Outcome,
+    trace::TraceEvent,
+};
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2em auto; max-width: 70em; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f3f3f3; }
+td ul { margin: 0; padding-left: 1.2em; }
+pre { background: #f6f6f6; padding: 1em; overflow-x: auto; }
+.solved { color: #1a7f37; }
+.failed { color: #cf222e; }
+tr.chosen { background: #e6f4ea; }
+.conflict { color: #cf222e; font-weight: bold; }
+.bar { background: #8ab4f8; height: 0.8em; }
+.tree ul { list-style: none; padding-left: 1.4em; border-left: 1px dotted #aaa; }
+.tree summary { cursor: pointer; }
+.muted { color: #888; }
+";
+
+/// The report for resolving `fixture` with the named `provider`. `trace` is
+/// the events recorded during resolution, if any.
+pub fn html(fixture: &Fixture, provider: &str, outcome: &Outcome, trace: &[TraceEvent]) -> String {
+    let title = format!(
+        "Resolving {} {}",
+        escape(&fixture.root),
+        fixture.root_version
+    );
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(
+        out,
+        "<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>"
+    );
+    let _ = writeln!(out, "<h1>{title}</h1>");
+    let _ = writeln!(
+        out,
+        "<p>Provider <code>{}</code>, {} packages, {} releases. Outcome: <strong class=\"{}\">{}</strong>.</p>",
+        escape(provider),
+        fixture.registry.package_count(),
+        fixture.registry.release_count(),
+        if outcome.is_solved() { "solved" } else { "failed" },
+        outcome.kind(),
+    );
+
+    root_requirements(&mut out, fixture);
+    outcome_section(&mut out, outcome);
+    registry_table(&mut out, fixture, outcome);
+    timeline(&mut out, trace);
+    dependency_graph(&mut out, fixture, outcome);
+
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+fn root_requirements(out: &mut String, fixture: &Fixture) {
+    let _ = writeln!(out, "<h2>Root requirements</h2>");
+    let Some(requirements) = fixture
+        .registry
+        .release(&fixture.root, &fixture.root_version)
+        .and_then(|release| release.requirements.as_ref())
+    else {
+        let _ = writeln!(
+            out,
+            "<p class=\"failed\">The root's requirements are unknown.</p>"
+        );
+        return;
+    };
+    let _ = writeln!(
+        out,
+        "<table>\n<tr><th>Package</th><th>Requirement</th><th>Releases admitted</th></tr>"
+    );
+    for (name, requirement) in requirements {
+        let admitted: Vec<_> = fixture
+            .registry
+            .releases(name)
+            .into_iter()
+            .flat_map(|releases| releases.keys())
+            .filter(|version| requirement.range.contains(version))
+            .map(ToString::to_string)
+            .collect();
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td><code>{}</code></td><td{}>{}</td></tr>",
+            escape(name),
+            escape(requirement.spec.as_str()),
+            if admitted.is_empty() {
+                " class=\"conflict\""
+            } else {
+                ""
+            },
+            if admitted.is_empty() {
+                "none".to_string()
+            } else {
+                escape(&admitted.join(", "))
+            },
+        );
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+fn outcome_section(out: &mut String, outcome: &Outcome) {
+    match outcome {
+        Outcome::Solved(solution) => {
+            let _ = writeln!(out, "<h2>Solution</h2>");
+            let _ = writeln!(out, "<table>\n<tr><th>Package</th><th>Version</th></tr>");
+            for (name, version) in solution {
+                let _ = writeln!(out, "<tr><td>{}</td><td>{version}</td></tr>", escape(name));
+            }
+            let _ = writeln!(out, "</table>");
+        }
+        Outcome::NoSolution(tree) => {
+            let _ = writeln!(out, "<h2>Explanation</h2>");
+            let _ = writeln!(
+                out,
+                "<pre>{}</pre>",
+                escape(&DefaultStringReporter::report(tree))
+            );
+        }
+        outcome => {
+            let _ = writeln!(out, "<h2>Explanation</h2>");
+            let _ = writeln!(out, "<pre>{}</pre>", escape(outcome.to_string().trim_end()));
+        }
+    }
+}
+
+fn registry_table(out: &mut String, fixture: &Fixture, outcome: &Outcome) {
+    let solution = match outcome {
+        Outcome::Solved(solution) => Some(solution),
+        _ => None,
+    };
+    let _ = writeln!(out, "<h2>Registry</h2>");
+    if solution.is_some() {
+        let _ = writeln!(out, "<p>Chosen releases are highlighted.</p>");
+    }
+    let _ = writeln!(
+        out,
+        "<table>\n<tr><th>Package</th><th>Version</th><th>Requirements</th></tr>"
+    );
+    for (name, releases) in fixture.registry.packages() {
+        for (version, release) in releases.iter().rev() {
+            let chosen = solution.is_some_and(|solution| solution.get(name) == Some(version));
+            let requirements = match &release.requirements {
+                None => "<span class=\"conflict\">unknown</span>".to_string(),
+                Some(requirements) if requirements.is_empty() => {
+                    "<span class=\"muted\">none</span>".to_string()
+                }
+                Some(requirements) => {
+                    let items: String = requirements
+                        .iter()
+                        .map(|(dependency, requirement)| {
+                            format!(
+                                "<li>{} <code>{}</code></li>",
+                                escape(dependency),
+                                escape(requirement.spec.as_str())
+                            )
+                        })
+                        .collect();
+                    format!("<ul>{items}</ul>")
+                }
+            };
+            let _ = writeln!(
+                out,
+                "<tr{}><td>{}</td><td>{version}</td><td>{requirements}</td></tr>",
+                if chosen { " class=\"chosen\"" } else { "" },
+                escape(name),
+            );
+        }
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+fn timeline(out: &mut String, trace: &[TraceEvent]) {
+    let _ = writeln!(out, "<h2>Solver trace</h2>");
+    let Some(last) = trace.last() else {
+        let _ = writeln!(out, "<p class=\"muted\">No events were recorded.</p>");
+        return;
+    };
+    let total = last.elapsed.as_secs_f64().max(f64::EPSILON);
+    let _ = writeln!(
+        out,
+        "<details>\n<summary>{} events over {:?}</summary>",
+        trace.len(),
+        last.elapsed
+    );
+    let _ = writeln!(
+        out,
+        "<table>\n<tr><th>Time</th><th></th><th>Level</th><th>Span</th><th>Event</th><th>Fields</th></tr>"
+    );
+    for event in trace {
+        let fields: Vec<_> = event
+            .fields
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        let _ = writeln!(
+            out,
+            "<tr><td>{:.3} ms</td><td style=\"width: 10em\"><div class=\"bar\" style=\"width: {:.1}%\"></div></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            event.elapsed.as_secs_f64() * 1000.0,
+            event.elapsed.as_secs_f64() / total * 100.0,
+            event.level,
+            event.span.unwrap_or(""),
+            escape(&event.message),
+            escape(&fields.join(" ")),
+        );
+    }
+    let _ = writeln!(out, "</table>\n</details>");
+}
+
+fn dependency_graph(out: &mut String, fixture: &Fixture, outcome: &Outcome) {
+    let _ = writeln!(out, "<h2>Dependency graph</h2>");
+    let (graph, root) = match outcome {
+        Outcome::Solved(solution) => {
+            let _ = writeln!(
+                out,
+                "<p>The chosen releases and the requirements between them.</p>"
+            );
+            let resolution = Resolution::from_solution(&fixture.root, &fixture.registry, solution);
+            (graph::resolution(&resolution), Some(0))
+        }
+        _ => {
+            let _ = writeln!(
+                out,
+                "<p>Every release each requirement admits, from the root down. \
+                 Requirements admitting no release are marked.</p>"
+            );
+            let graph = graph::registry(&fixture.registry, None);
+            let root = graph.nodes.iter().position(|node| {
+                node.package.as_deref() == Some(fixture.root.as_str())
+                    && node.version.as_ref() == Some(&fixture.root_version)
+            });
+            (graph, root)
+        }
+    };
+
+    if let Some(root) = root {
+        let _ = writeln!(
+            out,
+            "<div class=\"tree\">\n<details open>\n<summary>{} {}</summary>",
+            escape(&fixture.root),
+            fixture.root_version
+        );
+        let mut shown = HashSet::from([root]);
+        subtree(out, &graph, root, &mut shown);
+        let _ = writeln!(out, "</details>\n</div>");
+    }
+    let _ = writeln!(
+        out,
+        "<details>\n<summary>Graphviz source</summary>\n<pre>{}</pre>\n</details>",
+        escape(&graph.to_dot())
+    );
+}
+
+// Each release's requirements are listed under the first place it appears.
+fn subtree(out: &mut String, graph: &Graph, node: usize, shown: &mut HashSet<usize>) {
+    let edges: Vec<_> = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.from == node)
+        .collect();
+    if edges.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "<ul>");
+    for edge in edges {
+        let target = &graph.nodes[edge.to];
+        let label = format!(
+            "<code{}>{}</code> → {}",
+            if edge.conflict {
+                " class=\"conflict\""
+            } else {
+                ""
+            },
+            escape(&edge.requirement),
+            escape(&target.label()),
+        );
+        let has_children = graph.edges.iter().any(|other| other.from == edge.to);
+        if !has_children {
+            let _ = writeln!(out, "<li>{label}</li>");
+        } else if !shown.insert(edge.to) {
+            let _ = writeln!(
+                out,
+                "<li>{label} <span class=\"muted\">(expanded above)</span></li>"
+            );
+        } else {
+            let _ = writeln!(out, "<li><details>\n<summary>{label}</summary>");
+            subtree(out, graph, edge.to, shown);
+            let _ = writeln!(out, "</details></li>");
+        }
+    }
+    let _ = writeln!(out, "</ul>");
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
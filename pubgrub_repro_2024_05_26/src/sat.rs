@@ -0,0 +1,424 @@
+//! A second resolver backend: the [`cnf`](crate::cnf) encoding solved by a
+//! small embedded CDCL SAT solver instead of pubgrub, to unblock registries
+//! pubgrub panics on.
+//!
+//! Decisions follow what a resolver would do: take the first package, in name
+//! order, that a chosen release requires and that has no release chosen yet,
+//! and try its newest release not yet ruled out. Once nothing is left to
+//! satisfy, every other release is left out. Conflicts are learned from the
+//! first unique implication point, with backjumping and Luby restarts.
+
+use std::{collections::HashMap, time::Instant};
+
+use hexpm::version::Version;
+use pubgrub::type_aliases::SelectedDependencies;
+
+use crate::{
+    budget::{Budget, BudgetExceeded, Reason},
+    cnf::{self, Clause},
+    outcome::Outcome,
+    registry::Registry,
+    PackageName,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SatError {
+    #[error("no solution")]
+    NoSolution,
+    #[error(transparent)]
+    BudgetExceeded(#[from] BudgetExceeded),
+}
+
+/// Resolves `root` at `root_version`, returning the same shape of solution as
+/// `pubgrub::solver::resolve`. Conflicts and decisions count as steps against
+/// the budget.
+pub fn resolve(
+    registry: &Registry,
+    root: &str,
+    root_version: &Version,
+    budget: Budget,
+) -> Result<SelectedDependencies<PackageName, Version>, SatError> {
+    let span = tracing::info_span!("sat", root, %root_version);
+    let _entered = span.enter();
+
+    let cnf = cnf::encode(registry, root, root_version);
+    let mut solver = Solver::new(registry, &cnf, budget);
+    let model = solver.solve()?;
+    tracing::info!(
+        decisions = solver.decisions,
+        conflicts = solver.conflicts,
+        learnt = solver.clauses.len() - solver.original,
+        "finished"
+    );
+
+    let chosen = cnf
+        .variables
+        .iter()
+        .zip(model)
+        .filter(|(_, value)| *value)
+        .map(|((name, version), _)| (name.clone(), version.clone()))
+        .collect();
+    Ok(cnf.required(registry, &chosen).into_iter().collect())
+}
+
+/// [`resolve`] as an [`Outcome`], to sit alongside pubgrub's.
+pub fn outcome(registry: &Registry, root: &str, root_version: &Version, budget: Budget) -> Outcome {
+    match resolve(registry, root, root_version, budget) {
+        Ok(solution) => Outcome::Solved(solution.into_iter().collect()),
+        Err(SatError::NoSolution) => Outcome::Unsatisfiable,
+        Err(SatError::BudgetExceeded(exceeded)) => Outcome::TimedOut(exceeded),
+    }
+}
+
+// Variable `v` is literal `2v` when true and `2v + 1` when false.
+type Lit = usize;
+
+fn lit(dimacs: i32) -> Lit {
+    let var = dimacs.unsigned_abs() as usize - 1;
+    2 * var + usize::from(dimacs < 0)
+}
+
+fn var(lit: Lit) -> usize {
+    lit / 2
+}
+
+fn positive(lit: Lit) -> bool {
+    lit & 1 == 0
+}
+
+fn value(values: &[Option<bool>], lit: Lit) -> Option<bool> {
+    values[var(lit)].map(|value| value == positive(lit))
+}
+
+struct Solver {
+    clauses: Vec<Vec<Lit>>,
+    /// How many of `clauses` came from the formula rather than learning.
+    original: usize,
+    /// The clauses watching each literal, visited when it becomes false.
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+    /// Where on the trail each decision level starts.
+    trail_limits: Vec<usize>,
+    /// The next trail entry to propagate.
+    head: usize,
+    /// Each package's variables, newest release first.
+    packages: Vec<Vec<usize>>,
+    /// The packages each variable's release requires.
+    requires: Vec<Vec<usize>>,
+    /// Set when the formula is unsatisfiable before any decision.
+    inconsistent: bool,
+    budget: Budget,
+    started: Instant,
+    decisions: u64,
+    conflicts: u64,
+}
+
+impl Solver {
+    fn new(registry: &Registry, cnf: &cnf::Cnf, budget: Budget) -> Self {
+        let count = cnf.variables.len();
+        let mut packages: Vec<Vec<usize>> = Vec::new();
+        let mut package_of = Vec::with_capacity(count);
+        for (var, (name, _)) in cnf.variables.iter().enumerate() {
+            // Variables are grouped by package, oldest release first.
+            if var == 0 || cnf.variables[var - 1].0 != *name {
+                packages.push(Vec::new());
+            }
+            packages.last_mut().expect("pushed above").insert(0, var);
+            package_of.push(packages.len() - 1);
+        }
+        let index: HashMap<_, _> = cnf
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(var, (name, _))| (name.as_str(), package_of[var]))
+            .collect();
+        let requires = cnf
+            .variables
+            .iter()
+            .map(|(name, version)| {
+                registry
+                    .release(name, version)
+                    .and_then(|release| release.requirements.as_ref())
+                    .into_iter()
+                    .flat_map(|requirements| requirements.keys())
+                    .filter_map(|dependency| index.get(dependency.as_str()).copied())
+                    .collect()
+            })
+            .collect();
+
+        let mut solver = Self {
+            clauses: Vec::new(),
+            original: 0,
+            watches: vec![Vec::new(); 2 * count],
+            values: vec![None; count],
+            levels: vec![0; count],
+            reasons: vec![None; count],
+            trail: Vec::new(),
+            trail_limits: Vec::new(),
+            head: 0,
+            packages,
+            requires,
+            inconsistent: false,
+            budget,
+            started: Instant::now(),
+            decisions: 0,
+            conflicts: 0,
+        };
+        for clause in &cnf.clauses {
+            solver.add_clause(clause);
+        }
+        solver.original = solver.clauses.len();
+        solver
+    }
+
+    fn add_clause(&mut self, clause: &Clause) {
+        let mut lits: Vec<_> = clause.iter().map(|&literal| lit(literal)).collect();
+        lits.sort_unstable();
+        lits.dedup();
+        if lits.windows(2).any(|pair| var(pair[0]) == var(pair[1])) {
+            return;
+        }
+        match lits[..] {
+            [] => self.inconsistent = true,
+            [unit] => match value(&self.values, unit) {
+                None => self.enqueue(unit, None),
+                Some(true) => {}
+                Some(false) => self.inconsistent = true,
+            },
+            _ => {
+                self.watch(self.clauses.len(), &lits);
+                self.clauses.push(lits);
+            }
+        }
+    }
+
+    fn watch(&mut self, index: usize, lits: &[Lit]) {
+        self.watches[lits[0]].push(index);
+        self.watches[lits[1]].push(index);
+    }
+
+    fn level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        let var = var(lit);
+        self.values[var] = Some(positive(lit));
+        self.levels[var] = self.level();
+        self.reasons[var] = reason;
+        self.trail.push(lit);
+    }
+
+    /// Every release chosen, or `NoSolution`.
+    fn solve(&mut self) -> Result<Vec<bool>, SatError> {
+        if self.inconsistent {
+            return Err(SatError::NoSolution);
+        }
+        let mut restarts = 0;
+        let mut until_restart = luby(restarts) * 100;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                self.step()?;
+                self.conflicts += 1;
+                if self.level() == 0 {
+                    return Err(SatError::NoSolution);
+                }
+                let (learnt, level) = self.analyze(conflict);
+                self.backtrack(level);
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                } else {
+                    let index = self.clauses.len();
+                    self.watch(index, &learnt);
+                    self.enqueue(learnt[0], Some(index));
+                    self.clauses.push(learnt);
+                }
+                until_restart = until_restart.saturating_sub(1);
+                continue;
+            }
+
+            if until_restart == 0 {
+                restarts += 1;
+                until_restart = luby(restarts) * 100;
+                self.backtrack(0);
+                continue;
+            }
+
+            let Some(decision) = self.decide() else {
+                return Ok(self
+                    .values
+                    .iter()
+                    .map(|value| *value == Some(true))
+                    .collect());
+            };
+            self.step()?;
+            self.decisions += 1;
+            self.trail_limits.push(self.trail.len());
+            self.enqueue(decision, None);
+        }
+    }
+
+    fn step(&self) -> Result<(), BudgetExceeded> {
+        let steps = self.decisions + self.conflicts;
+        if let Some(max_steps) = self.budget.max_steps {
+            if steps >= max_steps {
+                return Err(BudgetExceeded {
+                    reason: Reason::StepLimit(max_steps),
+                    steps,
+                });
+            }
+        }
+        if let Some(timeout) = self.budget.timeout {
+            if self.started.elapsed() > timeout {
+                return Err(BudgetExceeded {
+                    reason: Reason::Timeout(timeout),
+                    steps,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Propagates every unit clause, returning the clause that conflicts, if
+    /// any.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.head < self.trail.len() {
+            let falsified = self.trail[self.head] ^ 1;
+            self.head += 1;
+
+            let mut watchers = std::mem::take(&mut self.watches[falsified]);
+            let mut i = 0;
+            while i < watchers.len() {
+                let index = watchers[i];
+                let clause = &mut self.clauses[index];
+                if clause[0] == falsified {
+                    clause.swap(0, 1);
+                }
+                if value(&self.values, clause[0]) == Some(true) {
+                    i += 1;
+                    continue;
+                }
+                if let Some(k) =
+                    (2..clause.len()).find(|&k| value(&self.values, clause[k]) != Some(false))
+                {
+                    clause.swap(1, k);
+                    self.watches[clause[1]].push(index);
+                    let _ = watchers.swap_remove(i);
+                    continue;
+                }
+                let unit = clause[0];
+                if value(&self.values, unit) == Some(false) {
+                    self.watches[falsified] = watchers;
+                    return Some(index);
+                }
+                self.enqueue(unit, Some(index));
+                i += 1;
+            }
+            self.watches[falsified] = watchers;
+        }
+        None
+    }
+
+    /// The clause learned from a conflict, asserting literal first and the
+    /// literal to watch at the backjump level second, and that level.
+    fn analyze(&self, conflict: usize) -> (Vec<Lit>, usize) {
+        let mut seen = vec![false; self.values.len()];
+        let mut learnt = vec![0];
+        let mut pending = 0;
+        let mut index = self.trail.len();
+        let mut lits = &self.clauses[conflict][..];
+        loop {
+            for &lit in lits {
+                let var = var(lit);
+                if seen[var] || self.levels[var] == 0 {
+                    continue;
+                }
+                seen[var] = true;
+                if self.levels[var] == self.level() {
+                    pending += 1;
+                } else {
+                    learnt.push(lit);
+                }
+            }
+            let implied = loop {
+                index -= 1;
+                if seen[var(self.trail[index])] {
+                    break self.trail[index];
+                }
+            };
+            pending -= 1;
+            if pending == 0 {
+                learnt[0] = implied ^ 1;
+                break;
+            }
+            let reason = self.reasons[var(implied)].expect("only decisions lack a reason");
+            // The implied literal comes first in its reason.
+            lits = &self.clauses[reason][1..];
+        }
+
+        let Some(second) = (1..learnt.len()).max_by_key(|&i| self.levels[var(learnt[i])]) else {
+            return (learnt, 0);
+        };
+        learnt.swap(1, second);
+        let level = self.levels[var(learnt[1])];
+        (learnt, level)
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        let Some(&start) = self.trail_limits.get(level) else {
+            return;
+        };
+        for lit in self.trail.drain(start..) {
+            self.values[var(lit)] = None;
+            self.reasons[var(lit)] = None;
+        }
+        self.trail_limits.truncate(level);
+        self.head = self.trail.len();
+    }
+
+    fn decide(&self) -> Option<Lit> {
+        let mut required = vec![false; self.packages.len()];
+        for &lit in &self.trail {
+            if positive(lit) {
+                for &package in &self.requires[var(lit)] {
+                    required[package] = true;
+                }
+            }
+        }
+        let unresolved = self
+            .packages
+            .iter()
+            .zip(required)
+            .find(|(variables, required)| {
+                *required && !variables.iter().any(|&var| self.values[var] == Some(true))
+            });
+        if let Some((variables, _)) = unresolved {
+            if let Some(&var) = variables.iter().find(|&&var| self.values[var].is_none()) {
+                return Some(2 * var);
+            }
+        }
+        self.values
+            .iter()
+            .position(Option::is_none)
+            .map(|var| 2 * var + 1)
+    }
+}
+
+// The Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, ... from zero.
+fn luby(mut i: u64) -> u64 {
+    let mut size = 1;
+    let mut power = 0;
+    while size < i + 1 {
+        power += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != i {
+        size = (size - 1) / 2;
+        power -= 1;
+        i %= size;
+    }
+    1 << power
+}
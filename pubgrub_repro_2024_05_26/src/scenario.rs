@@ -0,0 +1,360 @@
+//! Replaying a sequence of `gleam new`, `gleam remove` and `gleam add`
+//! commands on an in-memory gleam.toml and manifest.toml, to find the step at
+//! which a project gets into a state that fails to resolve.
+//!
+//! Locking follows Gleam 1.1. Once there is a manifest, every package in it
+//! that can be reached from a requirement unchanged since the manifest was
+//! written stays at its version, as an exact requirement of the root. Other
+//! packages are free to move, or to go. That is how the four steps in
+//! gleam_add_issue_2024_05_26's README build the root of the #3201 registry.
+//!
+//! Scripts hold one command per line, as typed. Lines starting with `#` are
+//! comments.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use hexpm::version::Version;
+
+use crate::{
+    fixture::Fixture,
+    manifest::{Manifest, ManifestError},
+    outcome::Outcome,
+    project::Project,
+    registry::{Registry, Release, Requirement},
+    PackageName,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// `gleam new NAME`
+    New { name: PackageName },
+    /// `gleam add [--dev] PACKAGE...`, each at the newest version that fits.
+    Add {
+        packages: Vec<PackageName>,
+        dev: bool,
+    },
+    /// `gleam remove PACKAGE...`
+    Remove { packages: Vec<PackageName> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("line {line}: expected `gleam new`, `gleam add` or `gleam remove`, found `{text}`")]
+pub struct ParseError {
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StepError {
+    #[error("there is no project yet, start with `gleam new`")]
+    NoProject,
+    #[error("there is already a project")]
+    ProjectExists,
+    #[error("{0} is not a dependency")]
+    NotADependency(PackageName),
+    #[error("{package} is locked to {version}, which `{requirement}` does not admit")]
+    IncompatibleLockedVersion {
+        package: PackageName,
+        version: Version,
+        requirement: String,
+    },
+    #[error("{}", .0.to_string().trim_end())]
+    Resolution(Outcome),
+    #[error(transparent)]
+    Manifest(#[from] ManifestError),
+}
+
+/// The project's files between steps.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub project: Project,
+    /// `None` until a step resolves, as `gleam new` writes no manifest.
+    pub manifest: Option<Manifest>,
+}
+
+#[derive(Debug)]
+pub struct StepReport {
+    pub step: Step,
+    /// The manifest's packages kept at their versions.
+    pub locked: BTreeMap<PackageName, Version>,
+    /// What was resolved, `None` if the step resolves nothing.
+    pub fixture: Option<Fixture>,
+    /// The manifest the step wrote.
+    pub manifest: Option<Manifest>,
+    pub result: Result<(), StepError>,
+}
+
+#[derive(Debug)]
+pub struct Run {
+    /// One for each step taken, ending at the first that failed.
+    pub steps: Vec<StepReport>,
+    /// The files after the last step that succeeded.
+    pub workspace: Option<Workspace>,
+}
+
+impl Run {
+    /// The failed step, numbered from 1.
+    pub fn failure(&self) -> Option<(usize, &StepReport)> {
+        let last = self.steps.last()?;
+        last.result.is_err().then_some((self.steps.len(), last))
+    }
+}
+
+pub fn parse(source: &str) -> Result<Vec<Step>, ParseError> {
+    let mut steps = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || ParseError {
+            line: index + 1,
+            text: line.to_string(),
+        };
+        let words: Vec<_> = line.split_whitespace().collect();
+        let (dev, packages): (Vec<&str>, Vec<&str>) =
+            words.iter().skip(2).partition(|word| **word == "--dev");
+        let packages: Vec<_> = packages.into_iter().map(str::to_string).collect();
+        if packages.is_empty() || packages.iter().any(|name| name.starts_with('-')) {
+            return Err(invalid());
+        }
+        let step = match (words[0], words[1], dev.is_empty(), packages.len()) {
+            ("gleam", "new", true, 1) => Step::New {
+                name: packages[0].clone(),
+            },
+            ("gleam", "add", dev, _) => Step::Add {
+                packages,
+                dev: !dev,
+            },
+            ("gleam", "remove", true, _) => Step::Remove { packages },
+            _ => return Err(invalid()),
+        };
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+/// Takes each step in turn against `registry`, resolving with `resolve`,
+/// until one fails.
+pub fn run(
+    steps: &[Step],
+    registry: &Registry,
+    mut resolve: impl FnMut(&Fixture) -> Outcome,
+) -> Run {
+    let mut workspace = None;
+    let mut reports = Vec::new();
+    for step in steps {
+        let mut report = StepReport {
+            step: step.clone(),
+            locked: BTreeMap::new(),
+            fixture: None,
+            manifest: None,
+            result: Ok(()),
+        };
+        match apply(
+            step,
+            workspace.as_ref(),
+            registry,
+            &mut resolve,
+            &mut report,
+        ) {
+            Ok(next) => workspace = Some(next),
+            Err(error) => report.result = Err(error),
+        }
+        let failed = report.result.is_err();
+        reports.push(report);
+        if failed {
+            break;
+        }
+    }
+    Run {
+        steps: reports,
+        workspace,
+    }
+}
+
+fn apply(
+    step: &Step,
+    workspace: Option<&Workspace>,
+    registry: &Registry,
+    resolve: &mut impl FnMut(&Fixture) -> Outcome,
+    report: &mut StepReport,
+) -> Result<Workspace, StepError> {
+    let workspace = match (step, workspace) {
+        (Step::New { name }, None) => return Ok(new_project(name)),
+        (Step::New { .. }, Some(_)) => return Err(StepError::ProjectExists),
+        (_, None) => return Err(StepError::NoProject),
+        (_, Some(workspace)) => workspace,
+    };
+    let mut project = workspace.project.clone();
+    let mut manifest = workspace.manifest.clone();
+
+    match step {
+        Step::New { .. } => unreachable!("handled above"),
+        Step::Remove { packages } => {
+            for package in packages {
+                let removed = project.dependencies.remove(package).is_some()
+                    || project.dev_dependencies.remove(package).is_some();
+                if !removed {
+                    return Err(StepError::NotADependency(package.clone()));
+                }
+                // `gleam remove` takes it out of the manifest's requirements
+                // too.
+                if let Some(manifest) = &mut manifest {
+                    let _ = manifest.requirements.remove(package);
+                }
+            }
+        }
+        Step::Add { packages, dev } => {
+            for package in packages {
+                let _ = project.dependencies.remove(package);
+                let _ = project.dev_dependencies.remove(package);
+                let requirement = Requirement::parse("*").expect("`*` is valid");
+                let table = match dev {
+                    true => &mut project.dev_dependencies,
+                    false => &mut project.dependencies,
+                };
+                let _ = table.insert(package.clone(), requirement);
+            }
+        }
+    }
+
+    if let Some(manifest) = &manifest {
+        report.locked = locked(&project, manifest);
+    }
+    let fixture = fixture(&project, registry, &report.locked)?;
+    report.fixture = Some(fixture.clone());
+    let solution = match resolve(&fixture) {
+        Outcome::Solved(solution) => solution,
+        outcome => return Err(StepError::Resolution(outcome)),
+    };
+    tracing::info!(%step, packages = solution.len() - 1, "resolved");
+
+    let mut added = BTreeSet::new();
+    if let Step::Add { packages, dev } = step {
+        // The version chosen, and any later release with the same major
+        // version.
+        for package in packages {
+            let version = &solution[package];
+            let requirement = format!(">= {version} and < {}.0.0", version.major + 1);
+            let requirement = Requirement::parse(&requirement).expect("generated requirement");
+            let table = match dev {
+                true => &mut project.dev_dependencies,
+                false => &mut project.dependencies,
+            };
+            let _ = table.insert(package.clone(), requirement);
+        }
+        // Only an existing manifest is edited in place.
+        if manifest.is_some() {
+            added.extend(packages.iter().cloned());
+        }
+    }
+    let mut manifest = Manifest::from_solution(&project, &fixture.registry, &solution)?;
+    manifest.added = added;
+    report.manifest = Some(manifest.clone());
+    Ok(Workspace {
+        project,
+        manifest: Some(manifest),
+    })
+}
+
+// The gleam.toml `gleam new` writes.
+fn new_project(name: &str) -> Workspace {
+    let requirement = |spec| Requirement::parse(spec).expect("template requirement");
+    Workspace {
+        project: Project {
+            name: name.to_string(),
+            version: Version::new(1, 0, 0),
+            dependencies: BTreeMap::from([(
+                "gleam_stdlib".to_string(),
+                requirement(">= 0.34.0 and < 2.0.0"),
+            )]),
+            dev_dependencies: BTreeMap::from([(
+                "gleeunit".to_string(),
+                requirement(">= 1.0.0 and < 2.0.0"),
+            )]),
+        },
+        manifest: None,
+    }
+}
+
+/// The packages in `manifest` that Gleam keeps at their versions when
+/// resolving `project`: those reachable from a requirement of the project
+/// that the manifest records unchanged.
+pub fn locked(project: &Project, manifest: &Manifest) -> BTreeMap<PackageName, Version> {
+    let mut locked = BTreeMap::new();
+    let mut stack: Vec<_> = project
+        .requirements(true)
+        .into_iter()
+        .filter(|(name, requirement)| {
+            manifest
+                .requirements
+                .get(name)
+                .is_some_and(|spec| spec == requirement.spec.as_str())
+        })
+        .map(|(name, _)| name)
+        .collect();
+    while let Some(name) = stack.pop() {
+        let Some(package) = manifest.package(&name) else {
+            continue;
+        };
+        if locked.insert(name, package.version.clone()).is_none() {
+            stack.extend(package.requirements.iter().cloned());
+        }
+    }
+    locked
+}
+
+// The project's requirements with every locked package pinned, as Gleam
+// hands them to pubgrub.
+fn fixture(
+    project: &Project,
+    registry: &Registry,
+    locked: &BTreeMap<PackageName, Version>,
+) -> Result<Fixture, StepError> {
+    let mut requirements = project.requirements(true);
+    for (package, version) in locked {
+        if let Some(requirement) = requirements.get(package) {
+            if !requirement.range.contains(version) {
+                return Err(StepError::IncompatibleLockedVersion {
+                    package: package.clone(),
+                    version: version.clone(),
+                    requirement: requirement.spec.as_str().to_string(),
+                });
+            }
+        }
+        let exact = Requirement::parse(&version.to_string()).expect("a version is a requirement");
+        let _ = requirements.insert(package.clone(), exact);
+    }
+
+    let mut registry = registry.clone();
+    registry.insert(
+        project.name.clone(),
+        project.version.clone(),
+        Release {
+            requirements: Some(requirements),
+            ..Release::default()
+        },
+    );
+    Ok(Fixture {
+        root: project.name.clone(),
+        root_version: project.version.clone(),
+        registry,
+    })
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::New { name } => write!(f, "gleam new {name}"),
+            Step::Add { packages, dev } => {
+                let dev = if *dev { " --dev" } else { "" };
+                write!(f, "gleam add{dev} {}", packages.join(" "))
+            }
+            Step::Remove { packages } => write!(f, "gleam remove {}", packages.join(" ")),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Each fixture's outcome, stored beside it as `<fixture>.snap`, so a change
+//! to pubgrub or a provider that alters any result shows up as a diff to
+//! review rather than passing unnoticed.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::outcome::Outcome;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Unchanged,
+    /// There is no snapshot yet.
+    New,
+    Changed {
+        /// Line by line, `-` for the snapshot and `+` for the outcome now.
+        diff: String,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("failed to access {path}: {source}")]
+pub struct SnapshotError {
+    path: String,
+    source: std::io::Error,
+}
+
+/// Where the snapshot of the fixture at `fixture` is kept.
+pub fn path(fixture: &Path) -> PathBuf {
+    fixture.with_extension("snap")
+}
+
+/// The outcome as stored: the solution in name order, pubgrub's explanation,
+/// or the error or panic message. Timeouts leave out the step count, which
+/// varies with the timeout.
+pub fn render(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::TimedOut(exceeded) => format!("timed out: {}\n", exceeded.reason),
+        outcome => outcome.to_string(),
+    }
+}
+
+/// Compares `rendered` with the fixture's snapshot.
+pub fn compare(fixture: &Path, rendered: &str) -> Result<Status, SnapshotError> {
+    let path = path(fixture);
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Status::New),
+        Err(source) => {
+            return Err(SnapshotError {
+                path: path.display().to_string(),
+                source,
+            })
+        }
+    };
+    Ok(if expected == rendered {
+        Status::Unchanged
+    } else {
+        Status::Changed {
+            diff: diff(&expected, rendered),
+        }
+    })
+}
+
+/// Makes `rendered` the fixture's snapshot.
+pub fn bless(fixture: &Path, rendered: &str) -> Result<(), SnapshotError> {
+    let path = path(fixture);
+    std::fs::write(&path, rendered).map_err(|source| SnapshotError {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+/// A line diff from `expected` to `actual`, with unchanged lines kept for
+/// context.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+
+    // common[i][j] is the longest common subsequence of the lines from i and j on.
+    let mut common = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            let _ = writeln!(out, "  {}", expected[i]);
+            (i, j) = (i + 1, j + 1);
+        } else if j == actual.len() || (i < expected.len() && common[i + 1][j] >= common[i][j + 1])
+        {
+            let _ = writeln!(out, "- {}", expected[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(out, "+ {}", actual[j]);
+            j += 1;
+        }
+    }
+    out
+}
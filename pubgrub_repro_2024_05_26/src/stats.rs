@@ -0,0 +1,181 @@
+//! Counting what pubgrub asks a provider, and optionally answering repeated
+//! dependency lookups from memory.
+//!
+//! For a repro, how often pubgrub asked about one release can say as much as
+//! the trace. For a provider that parses or decodes on every lookup, the
+//! cache saves that work a second time.
+
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    error::Error as StdError,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use hexpm::version::Version;
+use pubgrub::solver::{Dependencies, DependencyProvider};
+
+use crate::{PackageName, PubgrubRange};
+
+pub struct StatsDependencyProvider<DP> {
+    inner: DP,
+    /// `None` when caching is off. Only successful lookups are cached, so a
+    /// failed one is retried.
+    cache: Option<RefCell<Cache>>,
+    stats: RefCell<Stats>,
+}
+
+type Cache = HashMap<(PackageName, Version), Dependencies<PackageName, Version>>;
+
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub choose_calls: u64,
+    /// Spent in the wrapped provider's `choose_package_version`.
+    pub choose_time: Duration,
+    /// Every `get_dependencies` call, including those answered from the cache.
+    pub dependency_calls: u64,
+    /// Spent in the wrapped provider's `get_dependencies`.
+    pub dependency_time: Duration,
+    pub cache_hits: u64,
+    pub packages: BTreeMap<PackageName, PackageStats>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageStats {
+    /// How often `choose_package_version` picked the package.
+    pub chosen: u64,
+    /// How often the dependencies of each release were asked for.
+    pub lookups: BTreeMap<Version, u64>,
+}
+
+impl<DP> StatsDependencyProvider<DP> {
+    pub fn new(inner: DP, cache: bool) -> Self {
+        Self {
+            inner,
+            cache: cache.then(RefCell::default),
+            stats: RefCell::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats.borrow().clone()
+    }
+
+    pub fn into_inner(self) -> DP {
+        self.inner
+    }
+}
+
+impl<DP> DependencyProvider<PackageName, Version> for StatsDependencyProvider<DP>
+where
+    DP: DependencyProvider<PackageName, Version>,
+{
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn StdError>> {
+        let started = Instant::now();
+        let chosen = self.inner.choose_package_version(potential_packages);
+        let mut stats = self.stats.borrow_mut();
+        stats.choose_calls += 1;
+        stats.choose_time += started.elapsed();
+        if let Ok((name, _)) = &chosen {
+            stats
+                .packages
+                .entry(name.borrow().clone())
+                .or_default()
+                .chosen += 1;
+        }
+        chosen
+    }
+
+    fn get_dependencies(
+        &self,
+        name: &PackageName,
+        version: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn StdError>> {
+        {
+            let mut stats = self.stats.borrow_mut();
+            stats.dependency_calls += 1;
+            *stats
+                .packages
+                .entry(name.clone())
+                .or_default()
+                .lookups
+                .entry(version.clone())
+                .or_default() += 1;
+        }
+        let key = (name.clone(), version.clone());
+        if let Some(cached) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.borrow().get(&key).cloned())
+        {
+            self.stats.borrow_mut().cache_hits += 1;
+            return Ok(cached);
+        }
+
+        let started = Instant::now();
+        let dependencies = self.inner.get_dependencies(name, version);
+        self.stats.borrow_mut().dependency_time += started.elapsed();
+        if let (Some(cache), Ok(dependencies)) = (&self.cache, &dependencies) {
+            let _ = cache.borrow_mut().insert(key, dependencies.clone());
+        }
+        dependencies
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn StdError>> {
+        self.inner.should_cancel()
+    }
+}
+
+impl Stats {
+    /// Releases whose dependencies were asked for more than once, most asked
+    /// first.
+    pub fn repeated(&self) -> Vec<(&PackageName, &Version, u64)> {
+        let mut repeated: Vec<_> = self
+            .packages
+            .iter()
+            .flat_map(|(name, package)| {
+                package
+                    .lookups
+                    .iter()
+                    .map(move |(version, &count)| (name, version, count))
+            })
+            .filter(|(_, _, count)| *count > 1)
+            .collect();
+        repeated.sort_by_key(|(_, _, count)| Reverse(*count));
+        repeated
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} choose_package_version calls in {:?}",
+            self.choose_calls, self.choose_time
+        )?;
+        writeln!(
+            f,
+            "{} get_dependencies calls in {:?}, {} from the cache",
+            self.dependency_calls, self.dependency_time, self.cache_hits
+        )?;
+        writeln!(f, "package: chosen, dependency lookups")?;
+        for (name, package) in &self.packages {
+            let lookups: u64 = package.lookups.values().sum();
+            writeln!(f, "  {name}: {}, {lookups}", package.chosen)?;
+        }
+        let repeated = self.repeated();
+        if !repeated.is_empty() {
+            writeln!(f, "asked more than once:")?;
+            for (name, version, count) in repeated {
+                writeln!(f, "  {name} {version}: {count} times")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    borrow::Borrow,
+    cell::Cell,
+    error::Error as StdError,
+    fmt,
+    fs::File,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use hexpm::version::Version;
+use pubgrub::solver::{Dependencies, DependencyConstraints, DependencyProvider};
+use tracing::{
+    field::Field,
+    span::{Attributes, Id},
+    Event, Level, Subscriber,
+};
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    Layer,
+};
+
+use crate::{PackageName, PubgrubRange};
+
+/// Wraps a provider and emits a `tracing` event for every candidate set
+/// pubgrub offers, every decision made and every dependency set returned.
+///
+/// Decisions are numbered, so when pubgrub panics the log shows exactly which
+/// decisions led up to it.
+pub struct TracingDependencyProvider<DP> {
+    inner: DP,
+    decisions: Cell<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// A chronological plain text log.
+    Log,
+    /// Chrome trace event JSON, for `chrome://tracing` or Perfetto.
+    Chrome,
+}
+
+/// Keeps the subscriber's output flushing until dropped.
+pub struct TraceGuard {
+    _chrome: Option<FlushGuard>,
+}
+
+/// Install a global subscriber writing every solver event to `path`.
+pub fn install(path: &Path, format: TraceFormat) -> Result<TraceGuard, Box<dyn StdError>> {
+    match format {
+        TraceFormat::Log => {
+            tracing_subscriber::fmt()
+                .with_writer(Mutex::new(File::create(path)?))
+                .with_ansi(false)
+                .with_max_level(tracing::Level::TRACE)
+                .try_init()
+                .map_err(|error| error as Box<dyn StdError>)?;
+            Ok(TraceGuard { _chrome: None })
+        }
+        TraceFormat::Chrome => {
+            let (layer, guard) = ChromeLayerBuilder::new()
+                .writer(File::create(path)?)
+                .include_args(true)
+                .build();
+            tracing_subscriber::registry().with(layer).try_init()?;
+            Ok(TraceGuard {
+                _chrome: Some(guard),
+            })
+        }
+    }
+}
+
+/// One event captured by [`record`].
+#[derive(Debug, Clone)]
+pub struct TraceEvent {
+    /// Since recording started.
+    pub elapsed: Duration,
+    pub level: Level,
+    /// The innermost span the event was in, if any.
+    pub span: Option<&'static str>,
+    pub message: String,
+    /// The fields of the spans it was in, outermost first, then its own.
+    pub fields: Vec<(String, String)>,
+}
+
+/// Runs `f` with a subscriber on this thread that keeps every event in memory,
+/// for reports that embed the trace rather than write it to a file.
+pub fn record<T>(f: impl FnOnce() -> T) -> (T, Vec<TraceEvent>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(Recorder {
+        started: Instant::now(),
+        events: events.clone(),
+    });
+    let result = tracing::subscriber::with_default(subscriber, f);
+    let events = std::mem::take(&mut *events.lock().unwrap_or_else(|error| error.into_inner()));
+    (result, events)
+}
+
+struct Recorder {
+    started: Instant,
+    events: Arc<Mutex<Vec<TraceEvent>>>,
+}
+
+impl<S> Layer<S> for Recorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, context: Context<'_, S>) {
+        let mut visitor = Fields::default();
+        attributes.record(&mut visitor);
+        if let Some(span) = context.span(id) {
+            span.extensions_mut().insert(visitor);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, context: Context<'_, S>) {
+        let mut fields = Vec::new();
+        let mut span = None;
+        for scope in context
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+        {
+            if let Some(visitor) = scope.extensions().get::<Fields>() {
+                fields.extend(visitor.fields.iter().cloned());
+            }
+            span = Some(scope.name());
+        }
+        let mut visitor = Fields::default();
+        event.record(&mut visitor);
+        for field in visitor.fields {
+            // Events often repeat a field of their span.
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+        let event = TraceEvent {
+            elapsed: self.started.elapsed(),
+            level: *event.metadata().level(),
+            span,
+            message: visitor.message,
+            fields,
+        };
+        // A panic while holding the lock must not lose the events before it.
+        self.events
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .push(event);
+    }
+}
+
+#[derive(Default)]
+struct Fields {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl tracing::field::Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            self.fields
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .push((field.name().to_string(), value.to_string()));
+        }
+    }
+}
+
+impl<DP> TracingDependencyProvider<DP> {
+    pub fn new(inner: DP) -> Self {
+        Self {
+            inner,
+            decisions: Cell::new(0),
+        }
+    }
+
+    pub fn into_inner(self) -> DP {
+        self.inner
+    }
+}
+
+impl<DP> DependencyProvider<PackageName, Version> for TracingDependencyProvider<DP>
+where
+    DP: DependencyProvider<PackageName, Version>,
+{
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn StdError>> {
+        let decision = self.decisions.get() + 1;
+        self.decisions.set(decision);
+        let span = tracing::debug_span!("choose_package_version", decision);
+        let _entered = span.enter();
+
+        // Listing the candidates means collecting them, so only do it for a
+        // subscriber that wants them.
+        let chosen = if tracing::event_enabled!(Level::DEBUG) {
+            let candidates: Vec<_> = potential_packages.collect();
+            tracing::debug!(
+                count = candidates.len(),
+                candidates = %candidates
+                    .iter()
+                    .map(|(name, range)| format!("{} {}", name.borrow(), range.borrow()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                "candidates"
+            );
+            self.inner.choose_package_version(candidates.into_iter())
+        } else {
+            self.inner.choose_package_version(potential_packages)
+        };
+        match &chosen {
+            Ok((name, Some(version))) => {
+                tracing::info!(decision, package = %name.borrow(), %version, "decided")
+            }
+            Ok((name, None)) => {
+                tracing::info!(decision, package = %name.borrow(), "no version in range")
+            }
+            Err(error) => tracing::warn!(decision, %error, "choosing failed"),
+        }
+        chosen
+    }
+
+    fn get_dependencies(
+        &self,
+        name: &PackageName,
+        version: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn StdError>> {
+        let span = tracing::debug_span!("get_dependencies", package = %name, %version);
+        let _entered = span.enter();
+
+        let dependencies = self.inner.get_dependencies(name, version);
+        match &dependencies {
+            Ok(Dependencies::Known(constraints)) => tracing::debug!(
+                count = constraints.len(),
+                dependencies = %describe_constraints(constraints),
+                "dependencies"
+            ),
+            Ok(Dependencies::Unknown) => tracing::debug!("dependencies unknown"),
+            Err(error) => tracing::warn!(%error, "dependencies failed"),
+        }
+        dependencies
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn StdError>> {
+        self.inner.should_cancel()
+    }
+}
+
+// Field values are only evaluated when the event is enabled, so this costs
+// nothing unless a subscriber is listening.
+fn describe_constraints(constraints: &DependencyConstraints<PackageName, Version>) -> String {
+    let mut constraints: Vec<_> = constraints
+        .iter()
+        .map(|(name, range)| format!("{name} {range}"))
+        .collect();
+    constraints.sort();
+    constraints.join(", ")
+}
@@ -0,0 +1,115 @@
+//! Why a resolution contains a package: every chain of requirements from the
+//! root down to it.
+
+use std::{cmp::Reverse, fmt};
+
+use hexpm::version::Version;
+
+use crate::diff::{Edge, Resolution};
+
+/// A chain of requirements from the root to a package, root first. Each edge
+/// requires the package the next one comes from, and the last requires the
+/// package asked about.
+#[derive(Debug, Clone)]
+pub struct DependencyPath {
+    pub edges: Vec<Edge>,
+    pub package: String,
+    pub version: Option<Version>,
+}
+
+/// Every path from the root to `package`, shortest first. Cycles are not
+/// followed round.
+pub fn paths(resolution: &Resolution, package: &str) -> Vec<DependencyPath> {
+    let mut paths = Vec::new();
+    let mut chain = Vec::new();
+    walk(resolution, &mut vec![package], &mut chain, &mut paths);
+    paths.sort_by_key(Vec::len);
+    paths
+        .into_iter()
+        .map(|mut edges| {
+            edges.reverse();
+            DependencyPath {
+                edges,
+                package: package.to_string(),
+                version: resolution.versions.get(package).cloned(),
+            }
+        })
+        .collect()
+}
+
+// Builds chains from `package` upwards, so `chain` is leaf first and
+// `visited` holds the packages on it.
+fn walk<'a>(
+    resolution: &'a Resolution,
+    visited: &mut Vec<&'a str>,
+    chain: &mut Vec<Edge>,
+    paths: &mut Vec<Vec<Edge>>,
+) {
+    let package = visited.last().expect("walks start from a package");
+    let Some(edges) = resolution.dependents.get(*package) else {
+        return;
+    };
+    for edge in edges {
+        chain.push(edge.clone());
+        match &edge.from {
+            None => paths.push(chain.clone()),
+            Some((from, _)) if !visited.contains(&from.as_str()) => {
+                visited.push(from);
+                walk(resolution, visited, chain, paths);
+                let _ = visited.pop();
+            }
+            Some(_) => {}
+        }
+        let _ = chain.pop();
+    }
+}
+
+/// Of the edges requiring `package` directly, the one whose range admits the
+/// fewest of `available`, then the one inside the most other ranges. Edges
+/// without a known range are never the tightest.
+pub fn tightest<'a>(
+    resolution: &'a Resolution,
+    package: &str,
+    available: &[Version],
+) -> Option<&'a Edge> {
+    let ranges: Vec<_> = resolution
+        .dependents
+        .get(package)?
+        .iter()
+        .filter_map(|edge| Some((edge, &edge.requirement.as_ref()?.range)))
+        .collect();
+    ranges
+        .iter()
+        .min_by_key(|(_, range)| {
+            let admitted = available
+                .iter()
+                .filter(|version| range.contains(version))
+                .count();
+            let inside = ranges
+                .iter()
+                .filter(|(_, other)| range.intersection(other) == **range)
+                .count();
+            (admitted, Reverse(inside))
+        })
+        .map(|(edge, _)| *edge)
+}
+
+impl fmt::Display for DependencyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for edge in &self.edges {
+            match &edge.from {
+                Some((name, version)) => write!(f, "{name} {version}")?,
+                None => write!(f, "root")?,
+            }
+            if let Some(requirement) = &edge.requirement {
+                write!(f, " [{}]", requirement.spec.as_str())?;
+            }
+            write!(f, " → ")?;
+        }
+        write!(f, "{}", self.package)?;
+        match &self.version {
+            Some(version) => write!(f, " {version}"),
+            None => Ok(()),
+        }
+    }
+}
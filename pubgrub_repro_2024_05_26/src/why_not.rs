@@ -0,0 +1,235 @@
+//! Why a resolution holds a package back from its newest release, for filing
+//! requests to widen bounds upstream.
+
+use std::{collections::BTreeMap, fmt};
+
+use hexpm::version::Version;
+use pubgrub::report::{DefaultStringReporter, Reporter};
+use serde::{Serialize, Serializer};
+
+use crate::{
+    diff::Resolution,
+    fixture::Fixture,
+    outcome::Outcome,
+    registry::{Registry, Requirement},
+    PackageName, PubgrubRange,
+};
+
+/// A package resolved to something older than its newest release.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeldBack {
+    pub package: PackageName,
+    #[serde(serialize_with = "display")]
+    pub chosen: Version,
+    #[serde(serialize_with = "display")]
+    pub newest: Version,
+    /// Empty when nothing rules the newest release out and pubgrub simply
+    /// didn't pick it.
+    pub reasons: Vec<Reason>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Reason {
+    /// A requirement in the resolution does not admit the newest release.
+    Excluded {
+        /// `root`, or the requiring package and version.
+        by: String,
+        requirement: String,
+    },
+    /// The newest release requires a version of `dependency` the resolution
+    /// cannot have alongside it: no release in `requirement` meets the other
+    /// requirements on `dependency`.
+    Conflicts {
+        dependency: PackageName,
+        requirement: String,
+        chosen: String,
+    },
+    /// The registry doesn't know what the newest release requires.
+    UnknownDependencies,
+    /// No direct requirement rules it out, but requiring it exactly from the
+    /// root has no solution. `explanation` is pubgrub's report.
+    Transitive { explanation: String },
+}
+
+/// Every package in `solution` that is not on its newest release, with what
+/// keeps it there. Pre-releases only count as newest for packages already on
+/// a pre-release. Transitive conflicts are found by resolving again with
+/// `resolve`.
+pub fn held_back(
+    root: &str,
+    registry: &Registry,
+    solution: &BTreeMap<PackageName, Version>,
+    mut resolve: impl FnMut(&Fixture) -> Outcome,
+) -> Vec<HeldBack> {
+    let resolution = Resolution::from_solution(root, registry, solution);
+    solution
+        .iter()
+        .filter(|(name, _)| *name != root)
+        .filter_map(|(name, chosen)| {
+            let newest = registry
+                .releases(name)?
+                .keys()
+                .rev()
+                .find(|version| chosen.is_pre() || !version.is_pre())?;
+            if newest <= chosen {
+                return None;
+            }
+            Some(HeldBack {
+                package: name.clone(),
+                chosen: chosen.clone(),
+                newest: newest.clone(),
+                reasons: reasons(
+                    root,
+                    registry,
+                    solution,
+                    &resolution,
+                    name,
+                    newest,
+                    &mut resolve,
+                ),
+            })
+        })
+        .collect()
+}
+
+fn reasons(
+    root: &str,
+    registry: &Registry,
+    solution: &BTreeMap<PackageName, Version>,
+    resolution: &Resolution,
+    package: &str,
+    newest: &Version,
+    resolve: &mut impl FnMut(&Fixture) -> Outcome,
+) -> Vec<Reason> {
+    let mut reasons: Vec<_> = resolution
+        .dependents
+        .get(package)
+        .into_iter()
+        .flatten()
+        .filter_map(|edge| {
+            let requirement = edge.requirement.as_ref()?;
+            if requirement.range.contains(newest) {
+                return None;
+            }
+            Some(Reason::Excluded {
+                by: match &edge.from {
+                    Some((name, version)) => format!("{name} {version}"),
+                    None => "root".into(),
+                },
+                requirement: requirement.spec.as_str().to_string(),
+            })
+        })
+        .collect();
+
+    match registry
+        .release(package, newest)
+        .and_then(|release| release.requirements.as_ref())
+    {
+        None => reasons.push(Reason::UnknownDependencies),
+        Some(requirements) => {
+            reasons.extend(requirements.iter().filter_map(|(dependency, requirement)| {
+                let chosen = solution.get(dependency)?;
+                if requirement.range.contains(chosen) {
+                    return None;
+                }
+                // Moving to the newest release may move the dependency too,
+                // so only a range no release can meet alongside the rest of
+                // the resolution conflicts.
+                let others: Vec<_> = resolution
+                    .dependents
+                    .get(dependency)
+                    .into_iter()
+                    .flatten()
+                    .filter(|edge| !matches!(&edge.from, Some((name, _)) if name == package))
+                    .filter_map(|edge| edge.requirement.as_ref())
+                    .collect();
+                let fits = registry
+                    .releases(dependency)
+                    .into_iter()
+                    .flat_map(|releases| releases.keys())
+                    .any(|version| {
+                        requirement.range.contains(version)
+                            && others.iter().all(|other| other.range.contains(version))
+                    });
+                if fits {
+                    return None;
+                }
+                Some(Reason::Conflicts {
+                    dependency: dependency.clone(),
+                    requirement: requirement.spec.as_str().to_string(),
+                    chosen: chosen.to_string(),
+                })
+            }))
+        }
+    }
+
+    if reasons.is_empty() {
+        if let Some(explanation) =
+            pinned_conflict(root, registry, solution, package, newest, resolve)
+        {
+            reasons.push(Reason::Transitive { explanation });
+        }
+    }
+    reasons
+}
+
+// Resolve again with the root also requiring exactly `newest`.
+fn pinned_conflict(
+    root: &str,
+    registry: &Registry,
+    solution: &BTreeMap<PackageName, Version>,
+    package: &str,
+    newest: &Version,
+    resolve: &mut impl FnMut(&Fixture) -> Outcome,
+) -> Option<String> {
+    let root_version = &solution[root];
+    let mut release = registry.release(root, root_version)?.clone();
+    let spec = format!("== {newest}");
+    let _ = release
+        .requirements
+        .get_or_insert_with(BTreeMap::new)
+        .insert(
+            package.to_string(),
+            Requirement {
+                spec: hexpm::version::Range::new(spec),
+                range: PubgrubRange::exact(newest.clone()),
+            },
+        );
+    let mut registry = registry.clone();
+    registry.insert(root.to_string(), root_version.clone(), release);
+
+    let fixture = Fixture {
+        root: root.to_string(),
+        root_version: root_version.clone(),
+        registry,
+    };
+    match resolve(&fixture) {
+        Outcome::Solved(_) => None,
+        Outcome::NoSolution(tree) => Some(DefaultStringReporter::report(&tree)),
+        outcome => Some(outcome.to_string().trim_end().to_string()),
+    }
+}
+
+fn display<S: Serializer>(value: &impl fmt::Display, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Excluded { by, requirement } => write!(f, "{by} requires {requirement}"),
+            Reason::Conflicts {
+                dependency,
+                requirement,
+                chosen,
+            } => write!(
+                f,
+                "it requires {dependency} {requirement}, but {dependency} {chosen} is selected, \
+                 and no release in that range fits its other requirements"
+            ),
+            Reason::UnknownDependencies => write!(f, "its dependencies are unknown"),
+            Reason::Transitive { explanation } => write!(f, "{}", explanation.replace('\n', " ")),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Stopping a resolution when it runs out of budget.
+
+use std::time::Duration;
+
+use pubgrub_repro_2024_05_26::{
+    budget::{Budget, BudgetExceeded, BudgetedDependencyProvider, Reason},
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+};
+
+const REGISTRY: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+a = ">= 1.0.0"
+b = ">= 1.0.0"
+
+[packages.a."1.0.0".requirements]
+[packages.b."1.0.0".requirements]
+"#;
+
+fn resolve(budget: Budget, cancel: bool) -> Outcome {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let provider = BudgetedDependencyProvider::new(
+        InternedDependencyProvider::from_registry(&fixture.registry),
+        budget,
+    );
+    if cancel {
+        provider.cancel_handle().cancel();
+    }
+    outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+}
+
+#[test]
+fn an_ample_budget_changes_nothing() {
+    let budget = Budget {
+        timeout: Some(Duration::from_secs(60)),
+        max_steps: Some(100),
+    };
+    assert!(resolve(budget, false).is_solved());
+    assert!(resolve(Budget::default(), false).is_solved());
+    assert!(Budget::default().is_unlimited());
+}
+
+#[test]
+fn the_step_limit_stops_resolution() {
+    let budget = Budget {
+        max_steps: Some(2),
+        ..Budget::default()
+    };
+    let Outcome::TimedOut(exceeded) = resolve(budget, false) else {
+        panic!("expected a timeout");
+    };
+    assert_eq!(
+        exceeded,
+        BudgetExceeded {
+            reason: Reason::StepLimit(2),
+            steps: 2
+        }
+    );
+    assert_eq!(
+        exceeded.to_string(),
+        "hit the limit of 2 steps after 2 steps"
+    );
+}
+
+#[test]
+fn the_timeout_stops_resolution() {
+    let budget = Budget {
+        timeout: Some(Duration::ZERO),
+        ..Budget::default()
+    };
+    let outcome = resolve(budget, false);
+    assert!(
+        matches!(
+            &outcome,
+            Outcome::TimedOut(BudgetExceeded {
+                reason: Reason::Timeout(Duration::ZERO),
+                ..
+            })
+        ),
+        "{outcome}"
+    );
+    assert_eq!(outcome.kind(), "timed out");
+}
+
+#[test]
+fn cancelling_stops_resolution() {
+    let outcome = resolve(Budget::default(), true);
+    assert!(
+        matches!(
+            &outcome,
+            Outcome::TimedOut(BudgetExceeded {
+                reason: Reason::Cancelled,
+                steps: 0..=1,
+            })
+        ),
+        "{outcome}"
+    );
+}
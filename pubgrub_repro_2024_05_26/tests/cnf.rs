@@ -0,0 +1,124 @@
+//! DIMACS CNF encoding of registries, and decoding solver models.
+
+use pubgrub_repro_2024_05_26::{
+    cnf::{self, CnfError},
+    enumerate,
+    fixture::Fixture,
+};
+
+const TRADE_OFF: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+b = "*"
+a = "*"
+
+[packages.b."2.0.0".requirements]
+a = "< 2.0.0"
+
+[packages.b."1.0.0".requirements]
+[packages.a."2.0.0".requirements]
+[packages.a."1.0.0".requirements]
+"#;
+
+#[test]
+fn the_formula_has_a_clause_per_rule() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+    let cnf = cnf::encode(&fixture.registry, &fixture.root, &fixture.root_version);
+    assert_eq!(
+        cnf.to_dimacs(),
+        "c root app\n\
+         c 1 a 1.0.0\n\
+         c 2 a 2.0.0\n\
+         c 3 app 0.0.0\n\
+         c 4 b 1.0.0\n\
+         c 5 b 2.0.0\n\
+         p cnf 5 6\n\
+         3 0\n\
+         -1 -2 0\n\
+         -3 1 2 0\n\
+         -3 4 5 0\n\
+         -4 -5 0\n\
+         -5 1 0\n"
+    );
+}
+
+#[test]
+fn models_decode_in_either_format() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+    let cnf = cnf::encode(&fixture.registry, &fixture.root, &fixture.root_version);
+    let competition = cnf
+        .decode(
+            &fixture.registry,
+            "c kissat\ns SATISFIABLE\nv 1 -2 3 -4\nv 5 0\n",
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(competition["a"].to_string(), "1.0.0");
+    assert_eq!(competition["b"].to_string(), "2.0.0");
+
+    let minisat = cnf
+        .decode(&fixture.registry, "SAT\n2 3 4 0\n")
+        .unwrap()
+        .unwrap();
+    assert_eq!(minisat["a"].to_string(), "2.0.0");
+    assert_eq!(minisat["b"].to_string(), "1.0.0");
+
+    assert!(cnf
+        .decode(&fixture.registry, "s UNSATISFIABLE\n")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn models_that_break_a_clause_are_rejected() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+    let cnf = cnf::encode(&fixture.registry, &fixture.root, &fixture.root_version);
+    let error = cnf.decode(&fixture.registry, "SAT\n2 3 5 0\n").unwrap_err();
+    assert!(matches!(&error, CnfError::Unsatisfied(clause) if clause == "-5 1 0"));
+    assert!(matches!(
+        cnf.decode(&fixture.registry, "SAT\n6 0\n"),
+        Err(CnfError::UnknownVariable(6))
+    ));
+    assert!(matches!(
+        cnf.decode(&fixture.registry, "s UNKNOWN\n"),
+        Err(CnfError::Undecided(_))
+    ));
+}
+
+#[test]
+fn every_enumerated_solution_is_a_model() {
+    let fixture =
+        Fixture::from_toml(include_str!("../fixtures/gleam_add_issue_2024_05_26.toml")).unwrap();
+    let cnf = cnf::encode(&fixture.registry, &fixture.root, &fixture.root_version);
+    let solutions =
+        enumerate::solutions(&fixture.registry, &fixture.root, &fixture.root_version, 10);
+    assert!(!solutions.found.is_empty());
+    for solution in &solutions.found {
+        let model: Vec<_> = cnf
+            .variables
+            .iter()
+            .enumerate()
+            .filter(|(_, (name, version))| solution.get(name) == Some(version))
+            .map(|(i, _)| (i + 1).to_string())
+            .collect();
+        let decoded = cnf
+            .decode(&fixture.registry, &format!("SAT\n{} 0\n", model.join(" ")))
+            .unwrap();
+        assert_eq!(decoded.as_ref(), Some(solution));
+    }
+}
+
+#[test]
+fn releases_with_unknown_dependencies_are_never_chosen() {
+    let fixture = Fixture::issue_3201();
+    let cnf = cnf::encode(&fixture.registry, &fixture.root, &fixture.root_version);
+    let wisp = cnf
+        .variables
+        .iter()
+        .position(|(name, _)| name == "wisp")
+        .unwrap() as i32
+        + 1;
+    assert!(cnf.clauses.contains(&vec![-wisp]));
+}
@@ -0,0 +1,87 @@
+//! pubgrub's derivation tree as a graph.
+
+use hexpm::version::Version;
+use pubgrub::{
+    range::Range,
+    report::{DerivationTree, Derived, External},
+    type_aliases::Map,
+};
+use pubgrub_repro_2024_05_26::{
+    derivation::{DerivationGraph, NodeKind},
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+};
+
+#[test]
+fn a_failed_resolution_becomes_a_graph() {
+    let fixture = Fixture::from_toml(
+        r#"
+        [root]
+        name = "app"
+
+        [packages.app."0.0.0".requirements]
+        a = ">= 1.0.0"
+        b = "~> 1.0"
+
+        [packages.a."1.0.0".requirements]
+        b = "~> 2.0"
+
+        [packages.b."1.0.0".requirements]
+        [packages.b."2.0.0".requirements]
+        "#,
+    )
+    .unwrap();
+    let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+    let Outcome::NoSolution(tree) =
+        outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+    else {
+        panic!("expected no solution");
+    };
+
+    let graph = DerivationGraph::new(&tree);
+    let NodeKind::Derived { terms, causes, .. } = &graph.nodes[0].kind else {
+        panic!("the root is derived");
+    };
+    assert_eq!(terms.keys().collect::<Vec<_>>(), ["app"]);
+    assert!(causes.iter().all(|cause| *cause > 0));
+    assert!(graph.nodes.iter().any(|node| node.kind
+        == NodeKind::FromDependencyOf {
+            package: "a".into(),
+            range: "1.0.0".into(),
+            dependency: "b".into(),
+            dependency_range: "2.0.0 <= v < 3.0.0".into(),
+        }));
+
+    let json: serde_json::Value = serde_json::to_value(&graph).unwrap();
+    assert_eq!(json["nodes"][0]["id"], 0);
+    assert_eq!(json["nodes"][0]["kind"], "derived");
+    assert!(graph.to_dot().contains("n0 -> n"));
+}
+
+#[test]
+fn shared_incompatibilities_appear_once() {
+    let external = |package: &str| {
+        DerivationTree::External(External::NoVersions(package.to_string(), Range::any()))
+    };
+    let shared = DerivationTree::Derived(Derived {
+        terms: Map::default(),
+        shared_id: Some(7),
+        cause1: Box::new(external("a")),
+        cause2: Box::new(external("b")),
+    });
+    let tree: DerivationTree<String, Version> = DerivationTree::Derived(Derived {
+        terms: Map::default(),
+        shared_id: None,
+        cause1: Box::new(shared.clone()),
+        cause2: Box::new(shared),
+    });
+
+    let graph = DerivationGraph::new(&tree);
+    assert_eq!(graph.nodes.len(), 4);
+    assert!(matches!(
+        graph.nodes[0].kind,
+        NodeKind::Derived { causes: [1, 1], .. }
+    ));
+    assert!(graph.to_dot().contains("shared #7"));
+}
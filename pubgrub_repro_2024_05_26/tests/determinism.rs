@@ -0,0 +1,49 @@
+//! Resolving one fixture under many iteration orders.
+
+use std::path::Path;
+
+use pubgrub_repro_2024_05_26::{budget::Budget, determinism, fixture::Fixture, outcome};
+
+const TRADE_OFF: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+a = "*"
+b = "*"
+
+[packages.a."1.0.0".requirements]
+[packages.a."2.0.0".requirements]
+[packages.b."1.0.0".requirements]
+
+[packages.b."2.0.0".requirements]
+a = "< 2.0.0"
+"#;
+
+#[test]
+fn a_plain_solve_is_deterministic() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+    let report = determinism::check(&fixture, 50, 0, Budget::default());
+    assert!(report.is_deterministic());
+    assert_eq!(report.variants[0].seeds.len(), 50);
+    assert!(report.variants[0].outcome.contains("b 2.0.0"));
+}
+
+#[test]
+fn the_issue_3201_fixture_depends_on_iteration_order() {
+    let fixture = Fixture::load(Path::new("fixtures/issue_3201.toml")).unwrap();
+    let (report, again) = outcome::quietly(|| {
+        (
+            determinism::check(&fixture, 20, 0, Budget::default()),
+            determinism::check(&fixture, 20, 0, Budget::default()),
+        )
+    });
+
+    assert!(!report.is_deterministic());
+    assert!(report
+        .variants
+        .iter()
+        .any(|variant| variant.outcome.starts_with("panicked")));
+    // The same seeds replay the same outcomes.
+    assert_eq!(report.variants, again.variants);
+}
@@ -0,0 +1,124 @@
+//! Diffs between resolutions and the requirements that forced each change.
+
+use std::collections::BTreeMap;
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{
+    diff::{self, ChangeKind, Edge, Resolution},
+    fixture::Fixture,
+    manifest::Manifest,
+};
+
+const REGISTRY: &str = r#"
+[root]
+name = "app"
+version = "1.0.0"
+
+[packages.app."1.0.0".requirements]
+
+[packages.wisp."0.14.0".requirements]
+gleam_json = ">= 1.0.0 and < 2.0.0"
+gleam_stdlib = ">= 0.38.0 and < 2.0.0"
+
+[packages.gleam_json."0.7.0".requirements]
+[packages.gleam_json."1.0.1".requirements]
+[packages.gleam_stdlib."0.37.0".requirements]
+[packages.gleam_stdlib."0.38.0".requirements]
+[packages.gleeunit."1.1.2".requirements]
+"#;
+
+fn resolution(root_version: &str, solution: &[(&str, &str)]) -> Resolution {
+    let registry = Fixture::from_toml(REGISTRY).unwrap().registry;
+    let mut solution: BTreeMap<_, _> = solution
+        .iter()
+        .map(|(name, version)| (name.to_string(), Version::parse(version).unwrap()))
+        .collect();
+    let _ = solution.insert("app".into(), Version::parse(root_version).unwrap());
+    Resolution::from_solution("app", &registry, &solution)
+}
+
+#[test]
+fn reports_each_kind_of_change_with_its_cause() {
+    let old = resolution(
+        "1.0.0",
+        &[
+            ("gleam_json", "0.7.0"),
+            ("gleam_stdlib", "0.37.0"),
+            ("gleeunit", "1.1.2"),
+        ],
+    );
+    let new = resolution(
+        "1.0.0",
+        &[
+            ("gleam_json", "1.0.1"),
+            ("gleam_stdlib", "0.38.0"),
+            ("wisp", "0.14.0"),
+        ],
+    );
+
+    let changes = diff::diff(&old, &new);
+    let summary: Vec<_> = changes
+        .iter()
+        .map(|change| (change.package.as_str(), change.kind))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("gleam_json", ChangeKind::Upgraded),
+            ("gleam_stdlib", ChangeKind::Upgraded),
+            ("gleeunit", ChangeKind::Removed),
+            ("wisp", ChangeKind::Added),
+        ]
+    );
+
+    let forcing = changes[0].forcing_edges();
+    assert_eq!(forcing.len(), 1);
+    assert_eq!(
+        forcing[0].from,
+        Some(("wisp".into(), Version::new(0, 14, 0)))
+    );
+    assert_eq!(
+        changes[0].to_string(),
+        "upgraded gleam_json 0.7.0 -> 1.0.1\n  forced by wisp 0.14.0 (>= 1.0.0 and < 2.0.0)\n"
+    );
+}
+
+#[test]
+fn a_move_no_requirement_demands_is_not_forced() {
+    let old = resolution("1.0.0", &[("gleam_stdlib", "0.37.0")]);
+    let new = resolution("1.0.0", &[("gleam_stdlib", "0.38.0")]);
+
+    let changes = diff::diff(&old, &new);
+    assert!(changes[0].forcing_edges().is_empty());
+    assert!(changes[0].to_string().contains("not forced"));
+}
+
+#[test]
+fn a_move_with_an_unknown_requirement_is_not_called_unforced() {
+    let old = resolution("1.0.0", &[("gleam_stdlib", "0.37.0")]);
+    let mut new = resolution("1.0.0", &[("gleam_stdlib", "0.38.0")]);
+    let _ = new.dependents.insert(
+        "gleam_stdlib".into(),
+        vec![Edge {
+            from: None,
+            requirement: None,
+        }],
+    );
+
+    let changes = diff::diff(&old, &new);
+    assert!(changes[0].forcing_edges().is_empty());
+    assert_eq!(
+        changes[0].to_string(),
+        "upgraded gleam_stdlib 0.37.0 -> 0.38.0\n  required by the root, with an unknown requirement\n"
+    );
+}
+
+#[test]
+fn identical_manifests_have_no_changes() {
+    let manifest = Manifest::from_toml(include_str!(
+        "../../gleam_add_issue_2024_05_26/manifest.toml"
+    ))
+    .unwrap();
+    let resolution = Resolution::from_manifest(&manifest, None);
+    assert!(diff::diff(&resolution, &resolution).is_empty());
+}
@@ -0,0 +1,110 @@
+//! Brute-force enumeration of every solution, checked against pubgrub.
+
+use std::path::Path;
+
+use pubgrub_repro_2024_05_26::{
+    enumerate,
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+};
+
+const CHOICES: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+wisp = ">= 0.13.0"
+gleam_json = "*"
+
+[packages.wisp."0.14.0".requirements]
+gleam_json = "~> 1.0"
+
+[packages.wisp."0.13.0".requirements]
+gleam_json = ">= 0.6.0 and < 2.0.0"
+
+[packages.gleam_json."0.7.0".requirements]
+[packages.gleam_json."1.0.1".requirements]
+[packages.unrequired."1.0.0".requirements]
+"#;
+
+fn pubgrub(fixture: &Fixture) -> Outcome {
+    let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+    outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+}
+
+#[test]
+fn every_combination_is_found_and_includes_pubgrubs() {
+    let fixture = Fixture::from_toml(CHOICES).unwrap();
+    let solutions =
+        enumerate::solutions(&fixture.registry, &fixture.root, &fixture.root_version, 10);
+    assert!(solutions.complete);
+    let summary: Vec<_> = solutions
+        .found
+        .iter()
+        .map(|solution| {
+            (
+                solution["gleam_json"].to_string(),
+                solution["wisp"].to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("1.0.1".to_string(), "0.14.0".to_string()),
+            ("1.0.1".to_string(), "0.13.0".to_string()),
+            ("0.7.0".to_string(), "0.13.0".to_string()),
+        ]
+    );
+    assert!(solutions
+        .found
+        .iter()
+        .all(|solution| solution.len() == 3 && solution.contains_key("app")));
+
+    let Outcome::Solved(solution) = pubgrub(&fixture) else {
+        panic!("expected a solution");
+    };
+    assert!(solutions.found.contains(&solution));
+}
+
+#[test]
+fn the_cap_marks_counts_incomplete() {
+    let fixture = Fixture::from_toml(CHOICES).unwrap();
+    let capped = enumerate::count(&fixture.registry, &fixture.root, &fixture.root_version, 2);
+    assert_eq!((capped.count, capped.complete), (2, false));
+    let all = enumerate::count(&fixture.registry, &fixture.root, &fixture.root_version, 3);
+    assert_eq!((all.count, all.complete), (3, true));
+}
+
+#[test]
+fn issue_3201_has_no_solution() {
+    // wisp's dependencies are unknown, and the root requires it.
+    let fixture = Fixture::issue_3201();
+    let count = enumerate::count(&fixture.registry, &fixture.root, &fixture.root_version, 10);
+    assert_eq!((count.count, count.complete), (0, true));
+}
+
+#[test]
+fn the_gleam_add_fixture_has_exactly_pubgrubs_solution() {
+    let fixture =
+        Fixture::from_toml(include_str!("../fixtures/gleam_add_issue_2024_05_26.toml")).unwrap();
+    let solutions =
+        enumerate::solutions(&fixture.registry, &fixture.root, &fixture.root_version, 10);
+    let Outcome::Solved(solution) = pubgrub(&fixture) else {
+        panic!("expected a solution");
+    };
+    assert!(solutions.complete);
+    assert_eq!(solutions.found, [solution]);
+}
+
+#[test]
+fn the_panicking_registry_has_a_solution() {
+    // pubgrub 0.2.1 panics on this registry rather than finding it.
+    let fixture = Fixture::load(Path::new("fixtures/issue_3201_minimal.toml")).unwrap();
+    let solutions =
+        enumerate::solutions(&fixture.registry, &fixture.root, &fixture.root_version, 10);
+    assert!(solutions.complete);
+    assert_eq!(solutions.found.len(), 1);
+    assert_eq!(solutions.found[0]["glint"].to_string(), "1.0.0-rc3");
+}
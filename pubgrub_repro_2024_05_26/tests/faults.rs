@@ -0,0 +1,147 @@
+//! Resolving under injected provider faults.
+
+use std::path::Path;
+
+use pubgrub_repro_2024_05_26::{
+    budget::Budget,
+    faults::{self, FaultRun, Faults, FaultyDependencyProvider, Injected, Robustness},
+    fixture::{self, Fixture},
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+};
+
+const REGISTRY: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+glint = ">= 1.0.0 and < 2.0.0"
+
+[packages.glint."1.0.0".requirements]
+snag = "~> 0.3"
+
+[packages.snag."0.3.0".requirements]
+"#;
+
+const NONE: Faults = Faults {
+    seed: 0,
+    error: 0.0,
+    unknown: 0.0,
+    delay: 0.0,
+    delay_for: std::time::Duration::ZERO,
+    no_version: 0.0,
+};
+
+fn resolve(fixture: &Fixture, faults: Faults) -> (Outcome, Injected) {
+    let provider = FaultyDependencyProvider::new(
+        InternedDependencyProvider::from_registry(&fixture.registry),
+        faults,
+    );
+    let outcome = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+    (outcome, provider.injected())
+}
+
+#[test]
+fn without_faults_the_provider_is_transparent() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let (outcome, injected) = resolve(&fixture, NONE);
+    assert!(outcome.is_solved());
+    assert_eq!(injected, Injected::default());
+}
+
+#[test]
+fn each_fault_surfaces_as_an_error() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+
+    let (outcome, injected) = resolve(&fixture, Faults { error: 1.0, ..NONE });
+    assert!(
+        matches!(&outcome, Outcome::Failed(message) if message.contains("injected fault")),
+        "{outcome}"
+    );
+    assert_eq!(injected.errors, 1);
+
+    let (outcome, injected) = resolve(
+        &fixture,
+        Faults {
+            unknown: 1.0,
+            ..NONE
+        },
+    );
+    assert!(matches!(outcome, Outcome::NoSolution(_)), "{outcome}");
+    assert!(injected.unknown > 0);
+
+    let (outcome, injected) = resolve(
+        &fixture,
+        Faults {
+            no_version: 1.0,
+            ..NONE
+        },
+    );
+    assert!(matches!(outcome, Outcome::NoSolution(_)), "{outcome}");
+    assert_eq!(injected.no_versions, 1);
+}
+
+#[test]
+fn a_seed_replays_the_same_faults() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let faults = Faults {
+        seed: 7,
+        error: 0.3,
+        unknown: 0.3,
+        no_version: 0.3,
+        ..NONE
+    };
+    let (first, injected) = resolve(&fixture, faults);
+    let (second, again) = resolve(&fixture, faults);
+    assert_eq!(first.to_string(), second.to_string());
+    assert_eq!(injected, again);
+}
+
+#[test]
+fn faults_never_make_the_bundled_fixtures_panic() {
+    let faults = Faults {
+        error: 0.2,
+        unknown: 0.2,
+        no_version: 0.2,
+        ..Faults::default()
+    };
+    let mut new_panics = Vec::new();
+    for path in fixture::corpus(Path::new("fixtures")).unwrap() {
+        let fixture = Fixture::load(&path).unwrap();
+        let robustness =
+            outcome::quietly(|| faults::check(&fixture, faults, 20, Budget::default()));
+        new_panics.extend(robustness.new_panics().map(|run| (path.clone(), run.seed)));
+    }
+    assert!(new_panics.is_empty(), "{new_panics:?}");
+}
+
+#[test]
+fn only_panics_unlike_the_baseline_are_new() {
+    let run = |seed, outcome| FaultRun {
+        seed,
+        outcome,
+        injected: Injected::default(),
+    };
+    let runs = || {
+        vec![
+            run(0, Outcome::Panicked("known".into())),
+            run(1, Outcome::Panicked("other".into())),
+            run(2, Outcome::Solved(Default::default())),
+        ]
+    };
+    let seeds = |robustness: &Robustness| -> Vec<u64> {
+        robustness.new_panics().map(|run| run.seed).collect()
+    };
+
+    let panicking = Robustness {
+        baseline: Outcome::Panicked("known".into()),
+        runs: runs(),
+    };
+    assert_eq!(seeds(&panicking), [1]);
+
+    let solving = Robustness {
+        baseline: Outcome::Solved(Default::default()),
+        runs: runs(),
+    };
+    assert_eq!(seeds(&solving), [0, 1]);
+}
@@ -0,0 +1,108 @@
+//! DOT and Mermaid rendering of registries and resolutions.
+
+use std::collections::BTreeMap;
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{diff::Resolution, fixture::Fixture, graph, manifest::Manifest};
+
+const REGISTRY: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+glint = ">= 1.0.0 and < 2.0.0"
+snag = "~> 9.0"
+
+[packages.glint."1.0.0".requirements]
+[packages.glint."1.1.0".requirements]
+[packages.snag."0.3.0".requirements]
+"#;
+
+#[test]
+fn registries_link_every_admitted_release() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let solution = BTreeMap::from([
+        ("app".to_string(), Version::new(0, 0, 0)),
+        ("glint".to_string(), Version::new(1, 1, 0)),
+    ]);
+    let dot = graph::registry(&fixture.registry, Some(&solution)).to_dot();
+    assert_eq!(
+        dot,
+        r#"digraph dependencies {
+  rankdir=LR;
+  node [shape=box];
+  n0 [label="app 0.0.0", style=bold];
+  subgraph "cluster_glint" {
+    label="glint";
+    n1 [label="glint 1.0.0"];
+    n2 [label="glint 1.1.0", style=bold];
+  }
+  subgraph "cluster_snag" {
+    label="snag";
+    n3 [label="snag 0.3.0"];
+    n4 [label="snag (none)", style=dashed, color=red];
+  }
+  n0 -> n1 [label=">= 1.0.0 and < 2.0.0"];
+  n0 -> n2 [label=">= 1.0.0 and < 2.0.0"];
+  n0 -> n4 [label="~> 9.0", color=red, fontcolor=red, penwidth=2];
+}
+"#
+    );
+}
+
+#[test]
+fn mermaid_escapes_requirements_and_styles_conflicts() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let mermaid = graph::registry(&fixture.registry, None).to_mermaid();
+    assert_eq!(
+        mermaid,
+        r##"graph LR
+  n0["app 0.0.0"]
+  n1["glint 1.0.0"]
+  n2["glint 1.1.0"]
+  n3["snag 0.3.0"]
+  n4["snag (none)"]
+  n0 -->|"#gt;= 1.0.0 and #lt; 2.0.0"| n1
+  n0 -->|"#gt;= 1.0.0 and #lt; 2.0.0"| n2
+  n0 -->|"~#gt; 9.0"| n4
+  classDef missing stroke:red,stroke-dasharray:5
+  class n4 missing
+  linkStyle 2 stroke:red,stroke-width:2px,color:red
+"##
+    );
+}
+
+#[test]
+fn resolutions_flag_versions_their_requirements_exclude() {
+    // The manifest has snag 0.3.0, which the root's requirement rules out.
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let manifest = Manifest::from_toml(
+        r#"
+packages = [
+  { name = "glint", version = "1.1.0", build_tools = ["gleam"], requirements = [], otp_app = "glint", source = "hex", outer_checksum = "00" },
+  { name = "snag", version = "0.3.0", build_tools = ["gleam"], requirements = [], otp_app = "snag", source = "hex", outer_checksum = "00" },
+]
+
+[requirements]
+glint = { version = ">= 1.0.0 and < 2.0.0" }
+snag = { version = "~> 9.0" }
+"#,
+    )
+    .unwrap();
+    let graph = graph::resolution(&Resolution::from_manifest(
+        &manifest,
+        Some(&fixture.registry),
+    ));
+    let edges: Vec<_> = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            (
+                graph.nodes[edge.to].package.as_deref().unwrap(),
+                edge.conflict,
+            )
+        })
+        .collect();
+    assert_eq!(edges, [("glint", false), ("snag", true)]);
+    assert!(graph.nodes[graph.edges[0].from].package.is_none());
+}
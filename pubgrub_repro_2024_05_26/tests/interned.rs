@@ -0,0 +1,94 @@
+//! The interned provider answers exactly as the hash map one does.
+
+use std::path::Path;
+
+use pubgrub::{
+    range::Range,
+    solver::{Dependencies, DependencyProvider},
+};
+use pubgrub_repro_2024_05_26::{
+    fixture::{self, Fixture},
+    interned::InternedDependencyProvider,
+    issue_3201::Issue3201DependencyProvider,
+    outcome, snapshot, PackageName,
+};
+
+fn render(dependencies: Dependencies<PackageName, hexpm::version::Version>) -> Option<String> {
+    match dependencies {
+        Dependencies::Unknown => None,
+        Dependencies::Known(known) => {
+            let mut known: Vec<_> = known
+                .iter()
+                .map(|(name, range)| format!("{name} {range}"))
+                .collect();
+            known.sort();
+            Some(known.join(", "))
+        }
+    }
+}
+
+#[test]
+fn lookups_match_for_every_bundled_release() {
+    for path in fixture::corpus(Path::new("fixtures")).unwrap() {
+        let fixture = Fixture::load(&path).unwrap();
+        let hash_map = Issue3201DependencyProvider::from_registry(&fixture.registry);
+        let interned = InternedDependencyProvider::from_registry(&fixture.registry);
+
+        for (name, releases) in fixture.registry.packages() {
+            assert_eq!(hash_map.versions(name), interned.versions(name), "{name}");
+            for version in releases.keys() {
+                let expected = hash_map.get_dependencies(name, version).ok().map(render);
+                let actual = interned.get_dependencies(name, version).ok().map(render);
+                assert_eq!(expected, actual, "{name} {version}");
+
+                let candidates = [
+                    (name.clone(), Range::exact(version.clone())),
+                    (name.clone(), Range::higher_than(version.clone())),
+                ];
+                for candidate in candidates {
+                    let expected = hash_map
+                        .choose_package_version(std::iter::once(candidate.clone()))
+                        .unwrap();
+                    let actual = interned
+                        .choose_package_version(std::iter::once(candidate))
+                        .unwrap();
+                    assert_eq!(expected, actual, "{name} {version}");
+                }
+            }
+        }
+        // Both pick the package with the fewest versions, the first on a tie.
+        let everything = || {
+            fixture
+                .registry
+                .packages()
+                .map(|(name, _)| (name.clone(), Range::any()))
+        };
+        assert_eq!(
+            hash_map.choose_package_version(everything()).unwrap(),
+            interned.choose_package_version(everything()).unwrap()
+        );
+        assert!(hash_map.versions("missing").is_empty());
+        assert!(interned.versions("missing").is_empty());
+    }
+}
+
+#[test]
+fn outcomes_match_for_every_bundled_fixture() {
+    for path in fixture::corpus(Path::new("fixtures")).unwrap() {
+        let fixture = Fixture::load(&path).unwrap();
+        let hash_map = Issue3201DependencyProvider::from_registry(&fixture.registry);
+        let interned = InternedDependencyProvider::from_registry(&fixture.registry);
+        let (expected, actual) = outcome::quietly(|| {
+            (
+                outcome::resolve(&hash_map, &fixture.root, &fixture.root_version),
+                outcome::resolve(&interned, &fixture.root, &fixture.root_version),
+            )
+        });
+        assert_eq!(
+            snapshot::render(&expected),
+            snapshot::render(&actual),
+            "{}",
+            path.display()
+        );
+    }
+}
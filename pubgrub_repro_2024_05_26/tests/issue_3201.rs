@@ -0,0 +1,59 @@
+//! The bundled fixture is a faithful transcription of the hand-written
+//! registry from the issue.
+
+use hexpm::version::Version;
+use pubgrub::solver::{Dependencies, DependencyProvider};
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture, issue_3201::Issue3201DependencyProvider, PackageName,
+};
+
+fn dependencies(
+    provider: &Issue3201DependencyProvider,
+    name: &PackageName,
+    version: &Version,
+) -> Option<Vec<(PackageName, String)>> {
+    match provider.get_dependencies(name, version).ok()? {
+        Dependencies::Unknown => None,
+        Dependencies::Known(known) => {
+            let mut known: Vec<_> = known
+                .into_iter()
+                .map(|(name, range)| (name, range.to_string()))
+                .collect();
+            known.sort();
+            Some(known)
+        }
+    }
+}
+
+#[test]
+fn the_fixture_matches_the_hand_written_registry() {
+    let written = Issue3201DependencyProvider::new();
+    let fixture = Fixture::issue_3201();
+    let loaded = Issue3201DependencyProvider::from_registry(&fixture.registry);
+
+    let mut written_packages: Vec<_> = written.packages().collect();
+    let mut loaded_packages: Vec<_> = loaded.packages().collect();
+    written_packages.sort();
+    loaded_packages.sort();
+    assert_eq!(written_packages, loaded_packages);
+
+    for name in written_packages {
+        assert_eq!(written.versions(name), loaded.versions(name), "{name}");
+        for version in written.versions(name) {
+            assert_eq!(
+                dependencies(&written, name, version),
+                dependencies(&loaded, name, version),
+                "{name} {version}"
+            );
+        }
+    }
+}
+
+#[test]
+fn a_fixture_survives_a_round_trip_through_toml() {
+    let fixture = Fixture::issue_3201();
+    let again = Fixture::from_toml(&fixture.to_toml()).unwrap();
+    assert_eq!(again.root, fixture.root);
+    assert_eq!(again.root_version, fixture.root_version);
+    assert_eq!(again.to_toml(), fixture.to_toml());
+}
@@ -0,0 +1,179 @@
+//! Shrinking a fixture pubgrub fails on and writing it up as a bug report.
+
+use std::{collections::BTreeMap, path::Path};
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture,
+    issue_report::{self, Failure, Issue, IssueError},
+    minimise::minimise,
+    registry::{Release, Requirement},
+};
+
+// The #3201 panic, padded with releases and a package it does not need.
+fn padded() -> Fixture {
+    let mut fixture = Fixture::load(Path::new("fixtures/issue_3201_minimal.toml")).unwrap();
+    let stdlib = || {
+        (
+            "gleam_stdlib".to_string(),
+            Requirement::parse(">= 0.34.0 and < 2.0.0").unwrap(),
+        )
+    };
+    let registry = &mut fixture.registry;
+    for (name, version) in [("app", "0.0.0"), ("startest", "0.2.4")] {
+        let version = Version::parse(version).unwrap();
+        let mut release = registry.release(name, &version).unwrap().clone();
+        release
+            .requirements
+            .get_or_insert_with(BTreeMap::new)
+            .extend([stdlib()]);
+        registry.insert(name.to_string(), version, release);
+    }
+    let releases = [
+        ("startest", "0.2.3", Some(BTreeMap::from([stdlib()]))),
+        ("gleam_stdlib", "0.38.0", Some(BTreeMap::new())),
+        ("wisp", "0.14.0", None),
+    ];
+    for (name, version, requirements) in releases {
+        registry.insert(
+            name.to_string(),
+            Version::parse(version).unwrap(),
+            Release {
+                requirements,
+                ..Release::default()
+            },
+        );
+    }
+    fixture
+}
+
+#[test]
+fn minimising_keeps_only_what_the_failure_needs() {
+    let fixture = padded();
+    let issue = issue_report::prepare(&fixture, true).unwrap();
+    assert!(matches!(issue.failure, Failure::Panicked(_)));
+    assert!(issue.panic_location.is_some());
+
+    let minimised = &issue.minimised;
+    assert!(minimised.registry.release_count() < fixture.registry.release_count());
+    assert!(minimised.registry.releases("wisp").is_none());
+    assert!(minimised.registry.releases("gleam_stdlib").is_none());
+    assert_eq!(
+        issue_report::prepare(minimised, false).unwrap().failure,
+        issue.failure
+    );
+}
+
+#[test]
+fn minimise_stops_when_no_removal_keeps_the_failure() {
+    let fixture = padded();
+    let minimised = minimise(&fixture, |_| false);
+    assert_eq!(minimised.to_toml(), fixture.to_toml());
+}
+
+#[test]
+fn the_report_has_a_reproduction_and_versions() {
+    let issue = issue_report::prepare(&padded(), true).unwrap();
+    let markdown = issue.markdown();
+    for section in [
+        "## Reproduction",
+        "```rust",
+        "```toml",
+        "## Output",
+        "## Versions",
+    ] {
+        assert!(markdown.contains(section), "missing {section}");
+    }
+    assert!(markdown.contains("add_derivation should not be called after a decision"));
+    assert!(markdown.contains(&format!(
+        "| pubgrub | {} |",
+        issue_report::locked_version("pubgrub").unwrap()
+    )));
+}
+
+#[test]
+fn a_missed_solution_is_reported_as_found() {
+    let fixture = Fixture::from_toml(
+        r#"
+        [root]
+        name = "app"
+
+        [packages.app."0.0.0".requirements]
+        glint = ">= 1.0.0"
+
+        [packages.glint."1.0.0".requirements]
+        "#,
+    )
+    .unwrap();
+    let solution = BTreeMap::from([
+        ("app".to_string(), Version::new(0, 0, 0)),
+        ("glint".to_string(), Version::new(1, 0, 0)),
+    ]);
+    let issue = Issue {
+        original: fixture.clone(),
+        minimised: fixture,
+        failure: Failure::MissedSolution(solution),
+        panic_location: None,
+    };
+    let markdown = issue.markdown();
+    assert!(markdown.starts_with("# No solution reported for a solvable registry"));
+    assert!(markdown.contains("reports no solution, although there is one"));
+    assert!(markdown.contains("Yet this is a solution:\n\n- app 0.0.0\n- glint 1.0.0\n"));
+}
+
+#[test]
+fn there_is_nothing_to_report_when_the_fixture_resolves() {
+    let fixture = Fixture::from_toml(
+        r#"
+        [root]
+        name = "app"
+
+        [packages.app."0.0.0".requirements]
+        glint = ">= 1.0.0"
+
+        [packages.glint."1.0.0".requirements]
+        "#,
+    )
+    .unwrap();
+    assert!(matches!(
+        issue_report::prepare(&fixture, true),
+        Err(IssueError::Solved)
+    ));
+}
+
+#[test]
+fn there_is_nothing_to_report_when_the_sat_backend_agrees() {
+    let fixture = Fixture::from_toml(
+        r#"
+        [root]
+        name = "app"
+
+        [packages.app."0.0.0".requirements]
+        glint = ">= 2.0.0"
+
+        [packages.glint."1.0.0".requirements]
+        "#,
+    )
+    .unwrap();
+    assert!(matches!(
+        issue_report::prepare(&fixture, true),
+        Err(IssueError::NoSolution)
+    ));
+}
+
+#[test]
+fn each_thread_records_where_its_own_panic_happened() {
+    let locations: Vec<_> = std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..4)
+            .map(|_| scope.spawn(|| issue_report::prepare(&padded(), false).unwrap()))
+            .collect();
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap().panic_location)
+            .collect()
+    });
+    for location in locations {
+        let location = location.expect("a location for every panic");
+        assert!(location.contains("partial_solution.rs"), "{location}");
+    }
+}
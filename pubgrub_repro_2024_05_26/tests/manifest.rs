@@ -0,0 +1,68 @@
+//! manifest.toml output compared byte-for-byte with the manifests checked into
+//! the Gleam repro directories.
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    manifest::{Manifest, ManifestError},
+    outcome::{self, Outcome},
+    project::Project,
+};
+
+const REPROS: &[(&str, &str, &str, &str)] = &[
+    (
+        include_str!("../../formatter_issue_2024_05_13/gleam.toml"),
+        include_str!("../../formatter_issue_2024_05_13/manifest.toml"),
+        include_str!("../fixtures/formatter_issue_2024_05_13.toml"),
+        "",
+    ),
+    // startest was added with `gleam add --dev`.
+    (
+        include_str!("../../gleam_add_issue_2024_05_26/gleam.toml"),
+        include_str!("../../gleam_add_issue_2024_05_26/manifest.toml"),
+        include_str!("../fixtures/gleam_add_issue_2024_05_26.toml"),
+        "startest",
+    ),
+];
+
+#[test]
+fn checked_in_manifests_round_trip() {
+    for (_, manifest, _, _) in REPROS {
+        assert_eq!(Manifest::from_toml(manifest).unwrap().to_toml(), *manifest);
+    }
+}
+
+#[test]
+fn resolving_the_repros_writes_their_manifests() {
+    for (gleam_toml, expected, registry, added) in REPROS {
+        let project = Project::from_toml(gleam_toml).unwrap();
+        let fixture = project.fixture(&Fixture::from_toml(registry).unwrap().registry, true);
+        let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+        let Outcome::Solved(solution) =
+            outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+        else {
+            panic!("{} did not resolve", project.name);
+        };
+
+        let mut manifest = Manifest::from_solution(&project, &fixture.registry, &solution).unwrap();
+        manifest
+            .added
+            .extend(added.split_whitespace().map(String::from));
+        assert_eq!(manifest.to_toml(), *expected, "{}", project.name);
+    }
+}
+
+#[test]
+fn unknown_requirements_cannot_be_written() {
+    let project =
+        Project::from_toml(include_str!("../../gleam_add_issue_2024_05_26/gleam.toml")).unwrap();
+    let solution = [("gleam_json".to_string(), Version::new(1, 0, 1))].into();
+
+    let error =
+        Manifest::from_solution(&project, &Fixture::issue_3201().registry, &solution).unwrap_err();
+    assert!(
+        matches!(error, ManifestError::UnknownRequirements { ref package, .. } if package == "gleam_json"),
+        "{error}"
+    );
+}
@@ -0,0 +1,89 @@
+//! The newest solution, compared against pubgrub's.
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    optimal::{self, Stale},
+    outcome::{self, Outcome},
+};
+
+// Newest a only fits with older b, and the other way round.
+const TRADE_OFF: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+b = "*"
+a = "*"
+
+[packages.b."2.0.0".requirements]
+a = "< 2.0.0"
+
+[packages.b."1.0.0".requirements]
+[packages.a."2.0.0".requirements]
+[packages.a."1.0.0".requirements]
+[packages.c."1.0.0".requirements]
+"#;
+
+#[test]
+fn direct_dependencies_come_first_in_name_order() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+    let priority = optimal::priority(&fixture.registry, &fixture.root, &fixture.root_version);
+    assert_eq!(priority, ["a", "b", "c"]);
+
+    let newest = optimal::newest(&fixture.registry, &fixture.root, &fixture.root_version, 10);
+    assert!(newest.complete);
+    assert_eq!(newest.compared, 3);
+    let solution = newest.solution.unwrap();
+    assert_eq!(solution["a"].to_string(), "2.0.0");
+    assert_eq!(solution["b"].to_string(), "1.0.0");
+}
+
+#[test]
+fn pubgrub_settling_for_older_is_reported() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+    let newest = optimal::newest(&fixture.registry, &fixture.root, &fixture.root_version, 10)
+        .solution
+        .unwrap();
+    let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+    let Outcome::Solved(chosen) = outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+    else {
+        panic!("expected a solution");
+    };
+
+    // pubgrub 0.2.1 decides b first here and takes its newest release.
+    let stale = optimal::stale(
+        &fixture.registry,
+        &fixture.root,
+        &fixture.root_version,
+        &chosen,
+        &newest,
+    );
+    assert_eq!(
+        stale,
+        [Stale {
+            package: "a".into(),
+            chosen: Version::new(1, 0, 0),
+            newest: Version::new(2, 0, 0),
+            direct: true,
+        }]
+    );
+    assert_eq!(stale[0].to_string(), "a 1.0.0 could be 2.0.0 (direct)");
+}
+
+#[test]
+fn the_cap_marks_the_search_incomplete() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+    let newest = optimal::newest(&fixture.registry, &fixture.root, &fixture.root_version, 1);
+    assert!(!newest.complete);
+    assert_eq!(newest.compared, 1);
+}
+
+#[test]
+fn unsolvable_registries_have_no_newest_solution() {
+    let fixture = Fixture::issue_3201();
+    let newest = optimal::newest(&fixture.registry, &fixture.root, &fixture.root_version, 10);
+    assert!(newest.solution.is_none());
+    assert!(newest.complete);
+}
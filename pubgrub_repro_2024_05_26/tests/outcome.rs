@@ -0,0 +1,33 @@
+//! Keeping expected panics quiet without losing anyone else's.
+
+use std::panic;
+
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture,
+    issue_3201::Issue3201DependencyProvider,
+    outcome::{self, Outcome},
+};
+
+#[test]
+fn panics_are_caught_and_located() {
+    let fixture = Fixture::issue_3201();
+    let provider = Issue3201DependencyProvider::from_registry(&fixture.registry);
+    let outcome =
+        outcome::quietly(|| outcome::resolve(&provider, &fixture.root, &fixture.root_version));
+    assert!(matches!(outcome, Outcome::Panicked(_)), "{outcome}");
+    assert!(outcome::panic_location()
+        .unwrap()
+        .contains("partial_solution.rs"));
+}
+
+#[test]
+fn quiet_ends_even_when_the_closure_panics() {
+    let result = panic::catch_unwind(|| outcome::quietly(|| panic!("escaped")));
+    assert!(result.is_err());
+    assert!(outcome::panic_location()
+        .unwrap()
+        .contains("tests/outcome.rs"));
+    // Nested calls keep the outer setting too.
+    let value = outcome::quietly(|| outcome::quietly(|| 1) + 1);
+    assert_eq!(value, 2);
+}
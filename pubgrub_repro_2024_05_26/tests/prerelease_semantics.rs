@@ -0,0 +1,141 @@
+//! What `hexpm::version::Range::to_pubgrub` makes of pre-releases and build
+//! metadata, next to what the same requirement means to Hex.
+//!
+//! Each row is `(requirement, version, hex, pubgrub)`. Rows where the last two
+//! differ are known disagreements in the conversion; if one of them starts
+//! agreeing, hexpm has changed and the row should be updated.
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::hex_semantics::{self, HexRequirement};
+
+const MATRIX: &[(&str, &str, bool, bool)] = &[
+    // The requirement startest 0.2.4 places on glint.
+    (">= 1.0.0-rc2 and < 1.0.0-rc3", "1.0.0-rc1", false, false),
+    (">= 1.0.0-rc2 and < 1.0.0-rc3", "1.0.0-rc2", true, true),
+    (
+        ">= 1.0.0-rc2 and < 1.0.0-rc3",
+        "1.0.0-rc2+build",
+        true,
+        true,
+    ),
+    (">= 1.0.0-rc2 and < 1.0.0-rc3", "1.0.0-rc2.1", true, true),
+    (">= 1.0.0-rc2 and < 1.0.0-rc3", "1.0.0-rc3", false, false),
+    (">= 1.0.0-rc2 and < 1.0.0-rc3", "1.0.0", false, false),
+    // The root's requirement on glint. An exact version becomes
+    // `[version, next patch)`, which takes in every later pre-release and the
+    // release itself.
+    ("1.0.0-rc2", "1.0.0-rc2", true, true),
+    ("1.0.0-rc2", "1.0.0-rc2+build", true, true),
+    ("1.0.0-rc2", "1.0.0-rc3", false, true),
+    ("1.0.0-rc2", "1.0.0", false, true),
+    ("1.0.0-rc2", "1.0.1-rc1", false, true),
+    ("1.0.0-rc2", "1.0.1", false, false),
+    ("== 1.0.0", "1.0.0", true, true),
+    ("== 1.0.0", "1.0.0+build", true, true),
+    ("== 1.0.0", "1.0.0-rc1", false, false),
+    ("== 1.0.0", "1.0.1-rc1", false, true),
+    ("!= 1.0.0", "0.9.0", true, true),
+    ("!= 1.0.0", "1.0.0+build", false, false),
+    ("!= 1.0.0", "1.0.1-rc1", true, false),
+    // `>` and `<=` are converted by bumping the patch version, which moves the
+    // bound past the next patch's pre-releases.
+    ("> 1.0.0", "1.0.0+build", false, false),
+    ("> 1.0.0", "1.0.1-rc1", true, false),
+    ("> 1.0.0", "1.0.1", true, true),
+    ("<= 1.0.0", "1.0.0+build", true, true),
+    ("<= 1.0.0", "1.0.1-rc1", false, true),
+    // Ranges start at `0.0.0`, so nothing below it is ever included.
+    ("< 1.0.0", "0.0.0-rc1", true, false),
+    ("< 1.0.0", "1.0.0-rc1", true, true),
+    ("< 1.0.0", "1.0.0", false, false),
+    // Elixir's `~>` upper bound is the `-0` pre-release of the bump, hexpm's is
+    // the bump itself.
+    ("~> 1.2", "1.2.0-rc1", false, false),
+    ("~> 1.2", "1.2.0", true, true),
+    ("~> 1.2", "1.9.9", true, true),
+    ("~> 1.2", "2.0.0-0", false, true),
+    ("~> 1.2", "2.0.0-rc1", false, true),
+    ("~> 1.2", "2.0.0", false, false),
+    ("~> 1.2.3", "1.2.3", true, true),
+    ("~> 1.2.3", "1.2.9", true, true),
+    ("~> 1.2.3", "1.3.0-rc1", false, true),
+    ("~> 1.2.3", "1.3.0", false, false),
+    ("~> 1.0.0-rc2", "1.0.0-rc2", true, true),
+    ("~> 1.0.0-rc2", "1.0.5", true, true),
+    ("~> 1.0.0-rc2", "1.1.0-rc1", false, true),
+    ("~> 1.0.0-rc2", "1.1.0", false, false),
+    (">= 1.0.0 and < 1.1.0 or >= 2.0.0", "1.0.5", true, true),
+    (">= 1.0.0 and < 1.1.0 or >= 2.0.0", "1.5.0", false, false),
+    (">= 1.0.0 and < 1.1.0 or >= 2.0.0", "2.0.0", true, true),
+];
+
+#[test]
+fn matrix() {
+    for &(requirement, version, hex, pubgrub) in MATRIX {
+        let version = Version::parse(version).unwrap();
+        let comparison = hex_semantics::compare(requirement, &[version], true).unwrap();
+        assert_eq!(
+            (comparison[0].hex, comparison[0].pubgrub),
+            (hex, pubgrub),
+            "`{requirement}` against {}",
+            comparison[0].version,
+        );
+    }
+}
+
+#[test]
+fn disallowed_pre_releases_only_match_pre_release_operands() {
+    let matches = |requirement: &str, version: &str| {
+        HexRequirement::parse(requirement)
+            .unwrap()
+            .matches(&Version::parse(version).unwrap(), false)
+    };
+
+    assert!(!matches(">= 1.0.0", "1.1.0-rc1"));
+    assert!(matches(">= 1.0.0", "1.1.0"));
+    assert!(matches(">= 1.0.0-rc2 and < 1.0.0-rc3", "1.0.0-rc2"));
+    assert!(!matches("~> 1.2", "1.3.0-rc1"));
+}
+
+#[test]
+fn probes_straddle_every_bound() {
+    let probes: Vec<_> = HexRequirement::parse(">= 1.0.0-rc2 and < 1.0.0-rc3")
+        .unwrap()
+        .probe_versions()
+        .iter()
+        .map(Version::to_string)
+        .collect();
+
+    for expected in [
+        "1.0.0-rc1",
+        "1.0.0-rc2",
+        "1.0.0-rc2.1",
+        "1.0.0-rc3",
+        "1.0.0",
+    ] {
+        assert!(probes.iter().any(|probe| probe == expected), "{probes:?}");
+    }
+}
+
+#[test]
+fn probes_find_the_pessimistic_upper_bound_disagreement() {
+    let requirement = HexRequirement::parse("~> 1.2").unwrap();
+    let comparisons =
+        hex_semantics::compare("~> 1.2", &requirement.probe_versions(), true).unwrap();
+
+    let disagreements: Vec<_> = comparisons
+        .iter()
+        .filter(|comparison| !comparison.agrees())
+        .map(|comparison| comparison.version.to_string())
+        .collect();
+    assert_eq!(disagreements, ["2.0.0-0", "2.0.0-0.1", "2.0.0-rc1"]);
+}
+
+#[test]
+fn unparseable_requirements_are_errors() {
+    assert!(HexRequirement::parse("").is_err());
+    assert!(HexRequirement::parse(">=").is_err());
+    assert!(HexRequirement::parse("~> 1").is_err());
+    assert!(HexRequirement::parse(">= 1.0.0 and").is_err());
+    assert!(HexRequirement::parse(">= 1.0.0 1.2.0").is_err());
+}
@@ -0,0 +1,127 @@
+//! Resolving a gleam.toml with and without its dev-dependencies.
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture,
+    issue_3201::Issue3201DependencyProvider,
+    outcome::{self, Outcome},
+    project::{Project, ProjectError},
+};
+
+const REGISTRY: &str = r#"
+[root]
+name = "unused"
+
+[packages.unused."0.0.0".requirements]
+
+[packages.gleam_stdlib."0.38.0".requirements]
+
+[packages.argv."1.0.2".requirements]
+
+[packages.tom."0.3.0".requirements]
+gleam_stdlib = ">= 0.32.0 and < 2.0.0"
+
+[packages.startest."0.2.4".requirements]
+argv = "~> 1.0"
+gleam_stdlib = "~> 0.38"
+tom = "~> 0.3"
+"#;
+
+fn solve(project: &Project, include_dev: bool) -> Vec<String> {
+    let fixture = project.fixture(&Fixture::from_toml(REGISTRY).unwrap().registry, include_dev);
+    let provider = Issue3201DependencyProvider::from_registry(&fixture.registry);
+    match outcome::resolve(&provider, &fixture.root, &fixture.root_version) {
+        Outcome::Solved(solution) => solution.into_keys().collect(),
+        outcome => panic!("{outcome}"),
+    }
+}
+
+#[test]
+fn reads_both_tables_of_the_gleam_add_repro() {
+    let project =
+        Project::from_toml(include_str!("../../gleam_add_issue_2024_05_26/gleam.toml")).unwrap();
+
+    assert_eq!(project.name, "gleam_add_issue_2024_05_26");
+    assert_eq!(project.version, Version::new(1, 0, 0));
+    assert_eq!(
+        project.dependencies.keys().collect::<Vec<_>>(),
+        ["gleam_stdlib"]
+    );
+    assert_eq!(
+        project.dev_dependencies.keys().collect::<Vec<_>>(),
+        ["startest"]
+    );
+    assert_eq!(project.requirements(false).len(), 1);
+    assert_eq!(project.requirements(true).len(), 2);
+}
+
+#[test]
+fn dev_dependencies_are_only_resolved_when_asked_for() {
+    let project =
+        Project::from_toml(include_str!("../../gleam_add_issue_2024_05_26/gleam.toml")).unwrap();
+
+    assert_eq!(
+        solve(&project, false),
+        ["gleam_add_issue_2024_05_26", "gleam_stdlib"]
+    );
+    assert_eq!(
+        solve(&project, true),
+        [
+            "argv",
+            "gleam_add_issue_2024_05_26",
+            "gleam_stdlib",
+            "startest",
+            "tom"
+        ]
+    );
+}
+
+#[test]
+fn reports_packages_only_dev_dependencies_lead_to() {
+    let project = Project::from_toml(
+        r#"
+        name = "app"
+        version = "1.0.0"
+
+        [dependencies]
+        tom = "~> 0.3"
+
+        [dev-dependencies]
+        startest = "~> 0.2"
+        "#,
+    )
+    .unwrap();
+    let fixture = project.fixture(&Fixture::from_toml(REGISTRY).unwrap().registry, true);
+    let provider = Issue3201DependencyProvider::from_registry(&fixture.registry);
+    let Outcome::Solved(solution) =
+        outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+    else {
+        panic!("expected a solution");
+    };
+
+    // gleam_stdlib is also required by tom, so it is not dev-only.
+    assert_eq!(
+        project
+            .dev_only(&fixture.registry, &solution)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        ["argv", "startest"]
+    );
+}
+
+#[test]
+fn a_package_in_both_tables_is_an_error() {
+    let error = Project::from_toml(
+        r#"
+        name = "app"
+
+        [dependencies]
+        tom = "~> 0.3"
+
+        [dev-dependencies]
+        tom = "~> 0.3"
+        "#,
+    )
+    .unwrap_err();
+    assert!(matches!(error, ProjectError::DuplicateDependency(name) if name == "tom"));
+}
@@ -0,0 +1,134 @@
+//! Suggested relaxations for registries with no solution.
+
+use std::path::Path;
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{
+    budget::Budget,
+    fixture::Fixture,
+    outcome,
+    relax::{self, Bound, RelaxError, Suggestion},
+};
+
+fn fixture(root_requirement: &str) -> Fixture {
+    Fixture::from_toml(&format!(
+        r#"
+        [root]
+        name = "app"
+
+        [packages.app."0.0.0".requirements]
+        startest = "~> 0.2"
+        glint = "{root_requirement}"
+
+        [packages.startest."0.2.4".requirements]
+        glint = ">= 1.0.0-rc2 and < 1.0.0-rc3"
+
+        [packages.glint."1.0.0-rc2".requirements]
+        [packages.glint."1.0.0-rc3".requirements]
+        [packages.glint."1.0.0".requirements]
+        [packages.glint."1.1.0".requirements]
+        "#
+    ))
+    .unwrap()
+}
+
+fn summary(suggestions: &[Suggestion]) -> Vec<Vec<(&str, Bound)>> {
+    suggestions
+        .iter()
+        .map(|suggestion| {
+            suggestion
+                .relaxations
+                .iter()
+                .map(|relaxation| (relaxation.package.as_str(), relaxation.bound))
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn changes_are_suggested_on_either_side() {
+    let suggestions = outcome::quietly(|| {
+        relax::suggest(&fixture(">= 1.0.0-rc3 and < 1.0.0"), 2, Budget::default())
+    })
+    .unwrap();
+    assert_eq!(
+        summary(&suggestions),
+        [
+            vec![("app", Bound::Lower)],
+            vec![("startest", Bound::Upper)]
+        ]
+    );
+    assert_eq!(
+        suggestions[0].relaxations[0].to_string(),
+        "loosen the lower bound of app 0.0.0's requirement on glint \
+         (>= 1.0.0-rc3 and < 1.0.0) to admit 1.0.0-rc2"
+    );
+    assert_eq!(suggestions[0].panicked, None);
+    // Widening startest's upper bound resolves, but pubgrub 0.2.1 hits the
+    // #3201 panic on that registry.
+    assert_eq!(
+        suggestions[1].panicked.as_deref(),
+        Some("add_derivation should not be called after a decision")
+    );
+}
+
+#[test]
+fn widening_admits_only_the_next_release() {
+    // Admitting 1.0.0-rc3 must not let 1.0.0 in as well, so both sides have
+    // to move to meet at 1.0.0-rc3.
+    let suggestions = relax::suggest(&fixture("1.0.0"), 2, Budget::default()).unwrap();
+    assert_eq!(
+        summary(&suggestions),
+        [vec![("app", Bound::Lower), ("startest", Bound::Upper)]]
+    );
+    assert!(suggestions[0]
+        .relaxations
+        .iter()
+        .all(|relaxation| relaxation.admits.to_string() == "1.0.0-rc3"));
+}
+
+#[test]
+fn nothing_is_suggested_beyond_the_change_limit() {
+    assert!(relax::suggest(&fixture("1.0.0"), 1, Budget::default())
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn only_fixtures_pubgrub_finds_no_solution_for_are_relaxed() {
+    let solvable = relax::suggest(&fixture(">= 1.0.0-rc2"), 2, Budget::default());
+    assert!(matches!(solvable, Err(RelaxError::Solvable)));
+
+    let panics = outcome::quietly(|| {
+        relax::suggest(
+            &Fixture::load(Path::new("fixtures/issue_3201.toml")).unwrap(),
+            2,
+            Budget::default(),
+        )
+    });
+    assert!(
+        matches!(panics, Err(RelaxError::Baseline(message)) if message.starts_with("panicked"))
+    );
+}
+
+#[test]
+fn relaxed_requirements_are_hex_requirements() {
+    let fixture = fixture("1.0.0");
+    let suggestions = relax::suggest(&fixture, 2, Budget::default()).unwrap();
+    let registry = relax::relaxed(&fixture, &suggestions[0].relaxations);
+    let requirements = registry
+        .release("app", &Version::new(0, 0, 0))
+        .and_then(|release| release.requirements.as_ref())
+        .unwrap();
+    let spec = requirements["glint"].spec.as_str();
+    assert_eq!(spec, "1.0.0 or >= 1.0.0-rc3 and < 1.0.0");
+    let admitted: Vec<_> = ["1.0.0-rc2", "1.0.0-rc3", "1.0.0", "1.1.0"]
+        .into_iter()
+        .filter(|version| {
+            requirements["glint"]
+                .range
+                .contains(&Version::parse(version).unwrap())
+        })
+        .collect();
+    assert_eq!(admitted, ["1.0.0-rc3", "1.0.0"]);
+}
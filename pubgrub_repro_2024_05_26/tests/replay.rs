@@ -0,0 +1,105 @@
+//! Recording pubgrub's decisions and forcing them again.
+
+use std::path::Path;
+
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+    replay::{self, RecordingDependencyProvider, ReplayDependencyProvider, ReplayError},
+};
+
+const TRADE_OFF: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+a = "*"
+b = "*"
+
+[packages.a."1.0.0".requirements]
+[packages.a."2.0.0".requirements]
+[packages.b."1.0.0".requirements]
+
+[packages.b."2.0.0".requirements]
+a = "< 2.0.0"
+"#;
+
+fn replay(fixture: &Fixture, decisions: &str) -> (Outcome, usize) {
+    let provider = ReplayDependencyProvider::new(
+        InternedDependencyProvider::from_registry(&fixture.registry),
+        replay::parse(decisions).unwrap(),
+    );
+    let outcome = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+    (outcome, provider.replayed())
+}
+
+#[test]
+fn a_recording_replays_to_the_same_solution() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+    let recorder = RecordingDependencyProvider::new(InternedDependencyProvider::from_registry(
+        &fixture.registry,
+    ));
+    let recorded = outcome::resolve(&recorder, &fixture.root, &fixture.root_version);
+    let decisions = replay::write(&recorder.decisions());
+    assert_eq!(decisions, "app 0.0.0\nb 2.0.0\na 1.0.0\n");
+
+    let (replayed, count) = replay(&fixture, &decisions);
+    assert_eq!(replayed.to_string(), recorded.to_string());
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn forced_decisions_reach_the_panic() {
+    let fixture = Fixture::load(Path::new("fixtures/issue_3201_minimal.toml")).unwrap();
+    let (outcome, count) = outcome::quietly(|| {
+        replay(
+            &fixture,
+            "# root first\napp 0.0.0\nglint 1.0.0-rc3\nstartest 0.2.4\n",
+        )
+    });
+    assert!(
+        matches!(&outcome, Outcome::Panicked(message) if message.contains("add_derivation")),
+        "{outcome}"
+    );
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn a_different_request_fails_the_resolution() {
+    let fixture = Fixture::from_toml(TRADE_OFF).unwrap();
+
+    let (outcome, count) = replay(&fixture, "app 0.0.0\nc 1.0.0\n");
+    assert!(
+        matches!(&outcome, Outcome::Failed(message) if message.contains("decision 2: expected to decide c 1.0.0")),
+        "{outcome}"
+    );
+    assert_eq!(count, 1);
+
+    let (outcome, _) = replay(&fixture, "app 0.0.0\nb 2.0.0\na 3.0.0\n");
+    assert!(
+        matches!(&outcome, Outcome::Failed(message) if message.contains("outside the range")),
+        "{outcome}"
+    );
+
+    let (outcome, _) = replay(&fixture, "app 0.0.0\n");
+    assert!(
+        matches!(&outcome, Outcome::Failed(message) if message.contains("only 1 were recorded")),
+        "{outcome}"
+    );
+}
+
+#[test]
+fn malformed_lines_are_rejected() {
+    assert_eq!(
+        replay::parse("app 0.0.0\nglint\n"),
+        Err(ReplayError::InvalidLine {
+            line: 2,
+            text: "glint".into()
+        })
+    );
+    assert!(replay::parse("glint 1.0").is_err());
+    let decisions = replay::parse("wisp none\n").unwrap();
+    assert_eq!(decisions[0].version, None);
+    assert_eq!(replay::write(&decisions), "wisp none\n");
+}
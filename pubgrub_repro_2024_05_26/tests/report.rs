@@ -0,0 +1,63 @@
+//! HTML reports of a single run.
+
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture, interned::InternedDependencyProvider, outcome, report, trace,
+    trace::TracingDependencyProvider,
+};
+
+const REGISTRY: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+glint = ">= 1.0.0 and < 2.0.0"
+
+[packages.glint."1.0.0".requirements]
+snag = "~> 0.3"
+
+[packages.glint."2.0.0".requirements]
+[packages.snag."0.3.0".requirements]
+"#;
+
+#[test]
+fn a_solved_run_has_every_section() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let provider = TracingDependencyProvider::new(InternedDependencyProvider::from_registry(
+        &fixture.registry,
+    ));
+    let (outcome, events) =
+        trace::record(|| outcome::resolve(&provider, &fixture.root, &fixture.root_version));
+    assert!(events.iter().any(|event| event.message == "decided"
+        && event
+            .fields
+            .contains(&("package".to_string(), "glint".to_string()))));
+
+    let html = report::html(&fixture, "interned", &outcome, &events);
+    for heading in [
+        "Root requirements",
+        "Solution",
+        "Registry",
+        "Solver trace",
+        "Dependency graph",
+    ] {
+        assert!(html.contains(&format!("<h2>{heading}</h2>")), "{heading}");
+    }
+    assert!(html.contains("<code>&gt;= 1.0.0 and &lt; 2.0.0</code>"));
+    assert!(html.contains("<tr class=\"chosen\"><td>glint</td><td>1.0.0</td>"));
+    assert!(html.contains("<tr><td>glint</td><td>2.0.0</td>"));
+    assert!(!html.contains("<script"));
+}
+
+#[test]
+fn a_failed_run_explains_itself() {
+    let fixture = Fixture::from_toml(&REGISTRY.replace("~> 0.3", "~> 0.4")).unwrap();
+    let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+    let outcome = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+
+    let html = report::html(&fixture, "interned", &outcome, &[]);
+    assert!(html.contains("<h2>Explanation</h2>"));
+    assert!(html.contains("No events were recorded."));
+    // The tree starts from the root release, with the unsatisfiable
+    // requirement marked.
+    assert!(html.contains("<code class=\"conflict\">~&gt; 0.4</code> → snag (none)"));
+}
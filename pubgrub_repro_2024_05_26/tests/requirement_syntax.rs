@@ -0,0 +1,77 @@
+//! The requirement syntax fixtures accept, and the errors for the syntax they
+//! don't.
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{
+    registry::{Requirement, RequirementError},
+    PubgrubRange,
+};
+
+fn range(spec: &str) -> PubgrubRange {
+    Requirement::parse(spec).unwrap().range
+}
+
+fn version(version: &str) -> Version {
+    Version::parse(version).unwrap()
+}
+
+#[test]
+fn wildcard_accepts_any_version() {
+    assert_eq!(range("*"), PubgrubRange::any());
+    assert_eq!(range(" * "), PubgrubRange::any());
+    assert_eq!(Requirement::parse("*").unwrap().spec.as_str(), "*");
+}
+
+#[test]
+fn elixir_requirements_normalise_to_pubgrub_ranges() {
+    assert_eq!(
+        range("~> 1.2"),
+        PubgrubRange::between(version("1.2.0"), version("2.0.0"))
+    );
+    assert_eq!(
+        range("~> 1.2.3"),
+        PubgrubRange::between(version("1.2.3"), version("1.3.0"))
+    );
+    assert_eq!(range("== 1.2.3"), range("1.2.3"));
+    assert_eq!(range("!= 1.2.3"), range("1.2.3").negate());
+    assert_eq!(range(">=1.0.0"), range(">= 1.0.0"));
+
+    let either = range("~> 1.2 or ~> 3.0");
+    assert!(either.contains(&version("1.5.0")));
+    assert!(!either.contains(&version("2.5.0")));
+    assert!(either.contains(&version("3.1.0")));
+}
+
+#[test]
+fn unsupported_syntax_names_the_hex_equivalent() {
+    for (spec, found) in [
+        ("^1.2.0", "^"),
+        ("1.x", "1.x"),
+        ("1.2.*", "1.2.*"),
+        (">= 1.0.0 || >= 2.0.0", "||"),
+        (">= 1.0.0, < 2.0.0", ","),
+        ("~1.2.3", "~"),
+        ("= 1.0.0", "="),
+        ("1.2", "1.2"),
+        (">= 1.0.0 and *", "*"),
+    ] {
+        match Requirement::parse(spec) {
+            Err(RequirementError::Unsupported { found: actual, .. }) => {
+                assert_eq!(actual, found, "`{spec}`")
+            }
+            other => panic!("`{spec}` gave {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn malformed_requirements_are_errors() {
+    assert!(matches!(
+        Requirement::parse("  "),
+        Err(RequirementError::Empty)
+    ));
+    assert!(matches!(
+        Requirement::parse(">= 1.0.0 and"),
+        Err(RequirementError::Invalid(_))
+    ));
+}
@@ -0,0 +1,149 @@
+//! The embedded SAT backend, checked against pubgrub and brute force.
+
+use std::{collections::BTreeMap, path::Path};
+
+use hexpm::version::Version;
+use pubgrub_repro_2024_05_26::{
+    budget::{Budget, Reason},
+    enumerate,
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+    random::Random,
+    registry::{Registry, Release, Requirement},
+    sat::{self, SatError},
+};
+
+#[test]
+fn agrees_with_pubgrub_on_the_gleam_add_fixture() {
+    let fixture =
+        Fixture::from_toml(include_str!("../fixtures/gleam_add_issue_2024_05_26.toml")).unwrap();
+    let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+    let Outcome::Solved(expected) =
+        outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+    else {
+        panic!("expected a solution");
+    };
+    let solution = sat::resolve(
+        &fixture.registry,
+        &fixture.root,
+        &fixture.root_version,
+        Budget::default(),
+    )
+    .unwrap();
+    assert_eq!(solution.into_iter().collect::<BTreeMap<_, _>>(), expected);
+}
+
+#[test]
+fn solves_the_registry_pubgrub_panics_on() {
+    let fixture = Fixture::load(Path::new("fixtures/issue_3201_minimal.toml")).unwrap();
+    let solution = sat::resolve(
+        &fixture.registry,
+        &fixture.root,
+        &fixture.root_version,
+        Budget::default(),
+    )
+    .unwrap();
+    assert_eq!(solution["glint"].to_string(), "1.0.0-rc3");
+    assert_eq!(solution.len(), 3);
+}
+
+#[test]
+fn issue_3201_is_unsatisfiable() {
+    let fixture = Fixture::issue_3201();
+    let result = sat::resolve(
+        &fixture.registry,
+        &fixture.root,
+        &fixture.root_version,
+        Budget::default(),
+    );
+    assert!(matches!(result, Err(SatError::NoSolution)));
+}
+
+#[test]
+fn the_step_limit_stops_it() {
+    // Nothing forces a version of a here, so it takes a decision.
+    let fixture = Fixture::from_toml(
+        r#"
+        [root]
+        name = "app"
+
+        [packages.app."0.0.0".requirements]
+        a = "*"
+
+        [packages.a."1.0.0".requirements]
+        [packages.a."2.0.0".requirements]
+        "#,
+    )
+    .unwrap();
+    let budget = Budget {
+        max_steps: Some(0),
+        ..Budget::default()
+    };
+    let result = sat::resolve(
+        &fixture.registry,
+        &fixture.root,
+        &fixture.root_version,
+        budget,
+    );
+    assert!(matches!(
+        result,
+        Err(SatError::BudgetExceeded(exceeded)) if exceeded.reason == Reason::StepLimit(0)
+    ));
+}
+
+fn random_registry(random: &mut Random) -> Registry {
+    let names = ["a", "b", "c", "d", "e"];
+    let mut registry = Registry::new();
+    let add = |registry: &mut Registry, name: &str, version, random: &mut Random| {
+        let mut requirements = BTreeMap::new();
+        for dependency in names {
+            if dependency == name || random.below(3) != 0 {
+                continue;
+            }
+            let (low, high) = (random.below(4), random.below(4));
+            let spec = format!(">= {}.0.0 and < {}.0.0", low.min(high), low.max(high) + 1);
+            let _ = requirements.insert(dependency.to_string(), Requirement::parse(&spec).unwrap());
+        }
+        registry.insert(
+            name.to_string(),
+            version,
+            Release {
+                requirements: Some(requirements),
+                ..Release::default()
+            },
+        );
+    };
+    add(&mut registry, "root", Version::new(0, 0, 0), random);
+    for name in names {
+        for major in 0..random.below(4) {
+            add(
+                &mut registry,
+                name,
+                Version::new(major as u32, 0, 0),
+                random,
+            );
+        }
+    }
+    registry
+}
+
+#[test]
+fn agrees_with_brute_force_on_random_registries() {
+    // Seeded, so the registries are the same on every run.
+    let mut random = Random::new(0x5eed);
+    let root_version = Version::new(0, 0, 0);
+    for _ in 0..300 {
+        let registry = random_registry(&mut random);
+        let solutions = enumerate::solutions(&registry, "root", &root_version, 10_000);
+        assert!(solutions.complete);
+        match sat::resolve(&registry, "root", &root_version, Budget::default()) {
+            Ok(solution) => {
+                let solution: BTreeMap<_, _> = solution.into_iter().collect();
+                assert!(solutions.found.contains(&solution), "{solution:?}");
+            }
+            Err(SatError::NoSolution) => assert!(solutions.found.is_empty()),
+            Err(error) => panic!("{error}"),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Replaying gleam commands against an in-memory gleam.toml and manifest.toml.
+
+use std::path::Path;
+
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture,
+    issue_3201::Issue3201DependencyProvider,
+    manifest::Manifest,
+    outcome::{self, Outcome},
+    scenario::{self, ParseError, Step, StepError},
+};
+
+fn resolve(fixture: &Fixture) -> Outcome {
+    let provider = Issue3201DependencyProvider::from_registry(&fixture.registry);
+    outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+}
+
+#[test]
+fn the_readme_steps_panic_when_adding_wisp() {
+    let steps =
+        scenario::parse(include_str!("../scenarios/gleam_add_issue_2024_05_26.txt")).unwrap();
+    let registry = Fixture::load(Path::new("fixtures/gleam_add_issue_2024_05_26.toml"))
+        .unwrap()
+        .registry;
+
+    let run = outcome::quietly(|| scenario::run(&steps, &registry, resolve));
+
+    let (number, report) = run.failure().unwrap();
+    assert_eq!(number, 4);
+    assert_eq!(report.step.to_string(), "gleam add wisp");
+    assert!(matches!(
+        &report.result,
+        Err(StepError::Resolution(Outcome::Panicked(message)))
+            if message == "add_derivation should not be called after a decision"
+    ));
+    // Everything in the manifest is locked, as in the #3201 registry.
+    assert_eq!(report.locked.len(), 19);
+
+    // The files left behind are the ones checked in.
+    let workspace = run.workspace.unwrap();
+    assert_eq!(
+        workspace.manifest.unwrap(),
+        Manifest::load(Path::new("../gleam_add_issue_2024_05_26/manifest.toml")).unwrap()
+    );
+    assert_eq!(
+        workspace.project.dev_dependencies["startest"].spec.as_str(),
+        ">= 0.2.4 and < 1.0.0"
+    );
+    assert!(!workspace.project.dev_dependencies.contains_key("gleeunit"));
+}
+
+#[test]
+fn only_packages_reached_from_unchanged_requirements_are_locked() {
+    let registry = Fixture::from_toml(
+        r#"
+        [root]
+        name = "unused"
+
+        [packages.unused."0.0.0".requirements]
+
+        [packages.gleam_stdlib."0.38.0"]
+        outer_checksum = "00"
+
+        [packages.gleam_stdlib."0.38.0".requirements]
+
+        [packages.gleeunit."1.1.2"]
+        outer_checksum = "01"
+
+        [packages.gleeunit."1.1.2".requirements]
+        gleam_stdlib = ">= 0.33.0 and < 2.0.0"
+
+        [packages.argv."1.0.2"]
+        outer_checksum = "02"
+
+        [packages.argv."1.0.2".requirements]
+        "#,
+    )
+    .unwrap()
+    .registry;
+    let steps = scenario::parse(
+        "gleam new app\ngleam add argv\ngleam remove gleeunit\ngleam add --dev gleeunit\n",
+    )
+    .unwrap();
+    let run = scenario::run(&steps, &registry, resolve);
+    assert!(run.failure().is_none());
+
+    let locked: Vec<_> = run
+        .steps
+        .iter()
+        .map(|report| report.locked.keys().cloned().collect::<Vec<_>>())
+        .collect();
+    assert_eq!(
+        locked,
+        [
+            vec![],
+            vec![],
+            vec!["argv".to_string(), "gleam_stdlib".to_string()],
+            vec!["argv".to_string(), "gleam_stdlib".to_string()],
+        ]
+    );
+    let manifest = run.workspace.unwrap().manifest.unwrap();
+    assert_eq!(manifest.requirements["argv"], ">= 1.0.2 and < 2.0.0");
+    assert_eq!(manifest.requirements["gleeunit"], ">= 1.1.2 and < 2.0.0");
+    assert!(manifest.added.contains("gleeunit"));
+}
+
+#[test]
+fn steps_need_a_project_and_existing_dependencies() {
+    let registry = Fixture::issue_3201().registry;
+    let run = scenario::run(
+        &scenario::parse("gleam add wisp").unwrap(),
+        &registry,
+        resolve,
+    );
+    assert!(matches!(
+        run.failure().unwrap().1.result,
+        Err(StepError::NoProject)
+    ));
+
+    let run = scenario::run(
+        &scenario::parse("gleam new app\ngleam remove wisp").unwrap(),
+        &registry,
+        resolve,
+    );
+    assert!(matches!(
+        &run.failure().unwrap().1.result,
+        Err(StepError::NotADependency(name)) if name == "wisp"
+    ));
+}
+
+#[test]
+fn scripts_are_gleam_commands() {
+    assert_eq!(
+        scenario::parse("# setup\ngleam new app\n\ngleam add --dev startest glint\n").unwrap(),
+        [
+            Step::New { name: "app".into() },
+            Step::Add {
+                packages: vec!["startest".into(), "glint".into()],
+                dev: true,
+            },
+        ]
+    );
+    assert_eq!(
+        scenario::parse("gleam new app\ngleam build"),
+        Err(ParseError {
+            line: 2,
+            text: "gleam build".into()
+        })
+    );
+    assert!(scenario::parse("gleam remove --dev wisp").is_err());
+}
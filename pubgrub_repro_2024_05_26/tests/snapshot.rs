@@ -0,0 +1,51 @@
+//! Every bundled fixture still resolves the way its snapshot says. After an
+//! intended change, review the diff and run `cargo run -- snapshot --bless`.
+
+use std::path::Path;
+
+use pubgrub_repro_2024_05_26::{
+    fixture::{self, Fixture},
+    issue_3201::Issue3201DependencyProvider,
+    outcome,
+    snapshot::{self, Status},
+};
+
+#[test]
+fn the_bundled_fixtures_match_their_snapshots() {
+    let mut changed = Vec::new();
+    for path in fixture::corpus(Path::new("fixtures")).unwrap() {
+        let fixture = Fixture::load(&path).unwrap();
+        let provider = Issue3201DependencyProvider::from_registry(&fixture.registry);
+        let outcome =
+            outcome::quietly(|| outcome::resolve(&provider, &fixture.root, &fixture.root_version));
+        match snapshot::compare(&path, &snapshot::render(&outcome)).unwrap() {
+            Status::Unchanged => {}
+            status => changed.push(format!("{}: {status:?}", path.display())),
+        }
+    }
+    assert!(changed.is_empty(), "{}", changed.join("\n"));
+}
+
+#[test]
+fn a_diff_keeps_unchanged_lines_for_context() {
+    let diff = snapshot::diff(
+        "solved:\n  a 1.0.0\n  b 1.0.0\n",
+        "solved:\n  a 2.0.0\n  b 1.0.0\n  c 1.0.0\n",
+    );
+    assert_eq!(
+        diff,
+        "  solved:\n-   a 1.0.0\n+   a 2.0.0\n    b 1.0.0\n+   c 1.0.0\n"
+    );
+}
+
+#[test]
+fn snapshots_sit_beside_their_fixtures() {
+    assert_eq!(
+        snapshot::path(Path::new("fixtures/issue_3201.toml")),
+        Path::new("fixtures/issue_3201.snap")
+    );
+    assert_eq!(
+        snapshot::compare(Path::new("fixtures/missing.toml"), "").unwrap(),
+        Status::New
+    );
+}
@@ -0,0 +1,102 @@
+//! Provider call statistics and the dependency cache.
+
+use hexpm::version::Version;
+use pubgrub::solver::DependencyProvider;
+use pubgrub_repro_2024_05_26::{
+    budget::{Budget, BudgetedDependencyProvider},
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome,
+    stats::StatsDependencyProvider,
+};
+
+const REGISTRY: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+glint = ">= 1.0.0 and < 2.0.0"
+
+[packages.glint."1.0.0".requirements]
+snag = "~> 0.3"
+
+[packages.snag."0.3.0".requirements]
+[packages.wisp."0.14.0"]
+"#;
+
+fn provider(fixture: &Fixture) -> InternedDependencyProvider {
+    InternedDependencyProvider::from_registry(&fixture.registry)
+}
+
+#[test]
+fn a_resolution_is_counted_per_package() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let provider = StatsDependencyProvider::new(provider(&fixture), false);
+    let outcome = outcome::resolve(&provider, &fixture.root, &fixture.root_version);
+    assert!(outcome.is_solved());
+
+    let stats = provider.stats();
+    assert_eq!(stats.dependency_calls, 3);
+    assert_eq!(stats.cache_hits, 0);
+    assert_eq!(stats.packages["glint"].chosen, 1);
+    assert_eq!(stats.packages["snag"].lookups[&Version::new(0, 3, 0)], 1);
+    assert!(stats.repeated().is_empty());
+    assert!(stats.to_string().contains("3 get_dependencies calls"));
+}
+
+#[test]
+fn the_cache_answers_repeated_lookups() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let cached = StatsDependencyProvider::new(
+        StatsDependencyProvider::new(provider(&fixture), false),
+        true,
+    );
+    let snag = "snag".to_string();
+    let version = Version::new(0, 3, 0);
+    for _ in 0..3 {
+        let _ = cached.get_dependencies(&snag, &version).unwrap();
+    }
+
+    let stats = cached.stats();
+    assert_eq!(stats.dependency_calls, 3);
+    assert_eq!(stats.cache_hits, 2);
+    assert_eq!(stats.repeated(), [(&snag, &version, 3)]);
+    assert_eq!(cached.into_inner().stats().dependency_calls, 1);
+}
+
+#[test]
+fn failed_lookups_are_not_cached() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let cached = StatsDependencyProvider::new(
+        StatsDependencyProvider::new(provider(&fixture), false),
+        true,
+    );
+    let wisp = "wisp".to_string();
+    let version = Version::new(0, 14, 0);
+    assert!(cached.get_dependencies(&wisp, &version).is_err());
+    assert!(cached.get_dependencies(&wisp, &version).is_err());
+    assert_eq!(cached.stats().cache_hits, 0);
+    assert_eq!(cached.into_inner().stats().dependency_calls, 2);
+}
+
+#[test]
+fn cache_hits_still_take_budget_steps_inside_a_budget() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let snag = "snag".to_string();
+    let version = Version::new(0, 3, 0);
+    let steps = [false, true].map(|cache| {
+        let provider = BudgetedDependencyProvider::new(
+            StatsDependencyProvider::new(provider(&fixture), cache),
+            Budget::default(),
+        );
+        assert!(outcome::resolve(&provider, &fixture.root, &fixture.root_version).is_solved());
+        for _ in 0..2 {
+            let _ = provider.get_dependencies(&snag, &version).unwrap();
+        }
+        let steps = provider.steps();
+        let stats = provider.into_inner().stats();
+        assert_eq!(stats.cache_hits, if cache { 2 } else { 0 });
+        steps
+    });
+    assert_eq!(steps[0], steps[1]);
+}
@@ -0,0 +1,114 @@
+//! Tracing a resolution's decisions and provider calls.
+
+use std::{borrow::Borrow, cell::Cell, error::Error};
+
+use hexpm::version::Version;
+use pubgrub::{
+    range::Range,
+    solver::{Dependencies, DependencyProvider},
+};
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture, interned::InternedDependencyProvider, outcome, trace,
+    trace::TracingDependencyProvider, PackageName, PubgrubRange,
+};
+
+const REGISTRY: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+a = ">= 1.0.0"
+
+[packages.a."1.0.0".requirements]
+[packages.a."2.0.0".requirements]
+"#;
+
+#[test]
+fn every_decision_and_lookup_is_recorded() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let provider = TracingDependencyProvider::new(InternedDependencyProvider::from_registry(
+        &fixture.registry,
+    ));
+    let (outcome, events) =
+        trace::record(|| outcome::resolve(&provider, &fixture.root, &fixture.root_version));
+    assert!(outcome.is_solved(), "{outcome}");
+
+    let field = |event: &trace::TraceEvent, name: &str| {
+        event
+            .fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.clone())
+    };
+    let decided: Vec<_> = events
+        .iter()
+        .filter(|event| event.message == "decided")
+        .map(|event| {
+            format!(
+                "{} {} {}",
+                field(event, "decision").unwrap(),
+                field(event, "package").unwrap(),
+                field(event, "version").unwrap()
+            )
+        })
+        .collect();
+    assert_eq!(decided, ["1 app 0.0.0", "2 a 2.0.0"]);
+
+    let candidates: Vec<_> = events
+        .iter()
+        .filter(|event| event.message == "candidates")
+        .map(|event| field(event, "candidates").unwrap())
+        .collect();
+    assert_eq!(candidates, ["app 0.0.0", "a 1.0.0 <= v"]);
+
+    let dependencies: Vec<_> = events
+        .iter()
+        .filter(|event| event.message == "dependencies")
+        .map(|event| event.span)
+        .collect();
+    assert_eq!(
+        dependencies,
+        [Some("get_dependencies"), Some("get_dependencies")]
+    );
+}
+
+// Picks the first candidate offered without looking at the rest.
+struct First;
+
+impl DependencyProvider<PackageName, Version> for First {
+    fn choose_package_version<Name: Borrow<PackageName>, Ver: Borrow<PubgrubRange>>(
+        &self,
+        mut potential_packages: impl Iterator<Item = (Name, Ver)>,
+    ) -> Result<(Name, Option<Version>), Box<dyn Error>> {
+        let (name, _) = potential_packages.next().ok_or("no candidates")?;
+        Ok((name, None))
+    }
+
+    fn get_dependencies(
+        &self,
+        _: &PackageName,
+        _: &Version,
+    ) -> Result<Dependencies<PackageName, Version>, Box<dyn Error>> {
+        Ok(Dependencies::Unknown)
+    }
+}
+
+#[test]
+fn candidates_are_only_collected_for_a_subscriber() {
+    let provider = TracingDependencyProvider::new(First);
+    let taken = Cell::new(0);
+    let candidates = || {
+        ["a", "b", "c"]
+            .into_iter()
+            .map(|name| (name.to_string(), Range::any()))
+            .inspect(|_| taken.set(taken.get() + 1))
+    };
+
+    let _ = provider.choose_package_version(candidates()).unwrap();
+    assert_eq!(taken.get(), 1);
+
+    taken.set(0);
+    let (_, events) = trace::record(|| provider.choose_package_version(candidates()).unwrap());
+    assert_eq!(taken.get(), 3);
+    assert!(events.iter().any(|event| event.message == "candidates"));
+}
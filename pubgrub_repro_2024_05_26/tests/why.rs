@@ -0,0 +1,98 @@
+//! Dependency paths from the root to a package.
+
+use pubgrub_repro_2024_05_26::{
+    diff::Resolution,
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+    why,
+};
+
+fn resolve(source: &str) -> (Resolution, Fixture) {
+    let fixture = Fixture::from_toml(source).unwrap();
+    let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+    let Outcome::Solved(solution) =
+        outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+    else {
+        panic!("expected a solution");
+    };
+    let resolution = Resolution::from_solution(&fixture.root, &fixture.registry, &solution);
+    (resolution, fixture)
+}
+
+#[test]
+fn every_path_to_gleam_json_in_the_gleam_add_repro() {
+    let (resolution, _) = resolve(include_str!("../fixtures/gleam_add_issue_2024_05_26.toml"));
+    let paths: Vec<_> = why::paths(&resolution, "gleam_json")
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+    assert_eq!(
+        paths,
+        [
+            "root [>= 0.2.4 and < 1.0.0] → startest 0.2.4 [>= 1.4.0 and < 2.0.0] → \
+             gleam_community_ansi 1.4.0 [>= 1.3.0 and < 2.0.0] → \
+             gleam_community_colour 1.4.0 [>= 0.7.0 and < 2.0.0] → gleam_json 1.0.1",
+            "root [>= 0.2.4 and < 1.0.0] → startest 0.2.4 [>= 1.0.0-rc2 and < 1.0.0-rc3] → \
+             glint 1.0.0-rc2 [>= 1.0.0 and < 2.0.0] → \
+             gleam_community_colour 1.4.0 [>= 0.7.0 and < 2.0.0] → gleam_json 1.0.1",
+            "root [>= 0.2.4 and < 1.0.0] → startest 0.2.4 [>= 1.0.0-rc2 and < 1.0.0-rc3] → \
+             glint 1.0.0-rc2 [>= 1.0.0 and < 2.0.0] → \
+             gleam_community_ansi 1.4.0 [>= 1.3.0 and < 2.0.0] → \
+             gleam_community_colour 1.4.0 [>= 0.7.0 and < 2.0.0] → gleam_json 1.0.1",
+        ]
+    );
+}
+
+#[test]
+fn the_tightest_edge_admits_the_fewest_versions() {
+    let (resolution, fixture) = resolve(
+        r#"
+        [root]
+        name = "app"
+
+        [packages.app."0.0.0".requirements]
+        wisp = "~> 0.14"
+        gleam_json = ">= 0.1.0 and < 2.0.0"
+
+        [packages.wisp."0.14.0".requirements]
+        gleam_json = "~> 1.0"
+
+        [packages.gleam_json."0.7.0".requirements]
+        [packages.gleam_json."1.0.0".requirements]
+        [packages.gleam_json."1.0.1".requirements]
+        "#,
+    );
+    let available: Vec<_> = fixture
+        .registry
+        .releases("gleam_json")
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect();
+
+    let tightest = why::tightest(&resolution, "gleam_json", &available).unwrap();
+    assert_eq!(tightest.to_string(), "wisp 0.14.0 (~> 1.0)");
+}
+
+#[test]
+fn cycles_are_not_followed_round() {
+    let (resolution, _) = resolve(
+        r#"
+        [root]
+        name = "app"
+
+        [packages.app."0.0.0".requirements]
+        a = "1.0.0"
+
+        [packages.a."1.0.0".requirements]
+        b = "1.0.0"
+
+        [packages.b."1.0.0".requirements]
+        a = "1.0.0"
+        "#,
+    );
+    assert_eq!(why::paths(&resolution, "b").len(), 1);
+    assert_eq!(why::paths(&resolution, "a").len(), 1);
+}
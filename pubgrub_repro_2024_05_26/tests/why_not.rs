@@ -0,0 +1,152 @@
+//! What holds packages back from their newest release.
+
+use pubgrub_repro_2024_05_26::{
+    fixture::Fixture,
+    interned::InternedDependencyProvider,
+    outcome::{self, Outcome},
+    why_not::{self, HeldBack, Reason},
+};
+
+const REGISTRY: &str = r#"
+[root]
+name = "app"
+
+[packages.app."0.0.0".requirements]
+startest = "~> 0.2"
+wisp = "*"
+mist = "*"
+tom = "< 2.0.0"
+
+[packages.startest."0.2.4".requirements]
+gleam_json = ">= 0.7.0 and < 1.0.0"
+gleam_stdlib = "~> 0.36"
+
+[packages.wisp."0.14.0".requirements]
+gleam_json = "~> 1.0"
+
+[packages.wisp."0.13.0".requirements]
+gleam_json = ">= 0.6.0 and < 2.0.0"
+
+[packages.mist."1.2.0".requirements]
+glisten = "~> 2.0"
+
+[packages.mist."1.1.0".requirements]
+
+[packages.glisten."2.0.0".requirements]
+gleam_stdlib = "~> 1.0"
+
+[packages.tom."2.0.0".requirements]
+tom_core = "~> 1.1"
+
+[packages.tom."1.0.0".requirements]
+tom_core = "1.0.0"
+
+[packages.tom_core."1.1.0".requirements]
+[packages.tom_core."1.0.0".requirements]
+
+[packages.gleam_json."0.7.0".requirements]
+[packages.gleam_json."1.0.1".requirements]
+[packages.gleam_stdlib."0.38.0".requirements]
+[packages.gleam_stdlib."1.0.0".requirements]
+[packages.gleam_stdlib."1.1.0-rc1".requirements]
+"#;
+
+fn resolve(fixture: &Fixture) -> Outcome {
+    let provider = InternedDependencyProvider::from_registry(&fixture.registry);
+    outcome::resolve(&provider, &fixture.root, &fixture.root_version)
+}
+
+fn held_back() -> Vec<HeldBack> {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let Outcome::Solved(solution) = resolve(&fixture) else {
+        panic!("expected a solution");
+    };
+    why_not::held_back(&fixture.root, &fixture.registry, &solution, resolve)
+}
+
+fn find<'a>(held_back: &'a [HeldBack], package: &str) -> &'a HeldBack {
+    held_back
+        .iter()
+        .find(|row| row.package == package)
+        .unwrap_or_else(|| panic!("{package} is not held back"))
+}
+
+#[test]
+fn direct_requirements_that_exclude_the_newest_release() {
+    let held_back = held_back();
+    let gleam_json = find(&held_back, "gleam_json");
+    assert_eq!(gleam_json.newest.to_string(), "1.0.1");
+    assert_eq!(
+        gleam_json.reasons,
+        [Reason::Excluded {
+            by: "startest 0.2.4".into(),
+            requirement: ">= 0.7.0 and < 1.0.0".into(),
+        }]
+    );
+
+    // Pre-releases are not offered as upgrades from a release.
+    assert_eq!(find(&held_back, "gleam_stdlib").newest.to_string(), "1.0.0");
+}
+
+#[test]
+fn requirements_of_the_newest_release_that_conflict() {
+    let held_back = held_back();
+    assert_eq!(
+        find(&held_back, "wisp").reasons,
+        [Reason::Conflicts {
+            dependency: "gleam_json".into(),
+            requirement: "~> 1.0".into(),
+            chosen: "0.7.0".into(),
+        }]
+    );
+}
+
+#[test]
+fn a_dependency_that_can_move_along_does_not_conflict() {
+    let held_back = held_back();
+    // tom 2.0.0 needs a newer tom_core, which only tom 1.0.0 holds back.
+    assert_eq!(
+        find(&held_back, "tom").reasons,
+        [Reason::Excluded {
+            by: "root".into(),
+            requirement: "< 2.0.0".into(),
+        }]
+    );
+}
+
+#[test]
+fn conflicts_further_down_are_explained_by_pubgrub() {
+    let held_back = held_back();
+    let [Reason::Transitive { explanation }] = find(&held_back, "mist").reasons.as_slice() else {
+        panic!("expected a transitive conflict");
+    };
+    assert!(explanation.contains("glisten"), "{explanation}");
+}
+
+#[test]
+fn transitive_conflicts_resolve_with_the_given_backend() {
+    let fixture = Fixture::from_toml(REGISTRY).unwrap();
+    let Outcome::Solved(solution) = resolve(&fixture) else {
+        panic!("expected a solution");
+    };
+    let mut roots = Vec::new();
+    let held_back = why_not::held_back(&fixture.root, &fixture.registry, &solution, |fixture| {
+        roots.push(fixture.root.clone());
+        Outcome::Failed("backend unavailable".into())
+    });
+    assert_eq!(roots, ["app"]);
+    assert_eq!(
+        find(&held_back, "mist").reasons,
+        [Reason::Transitive {
+            explanation: "failed: backend unavailable".into(),
+        }]
+    );
+}
+
+#[test]
+fn json_names_each_reason() {
+    let json = serde_json::to_value(held_back()).unwrap();
+    assert_eq!(json[0]["package"], "gleam_json");
+    assert_eq!(json[0]["chosen"], "0.7.0");
+    assert_eq!(json[0]["reasons"][0]["kind"], "excluded");
+}